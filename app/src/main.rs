@@ -1,5 +1,5 @@
 use tokio;
-use lib_simulation::{SimulationConfig, IndividualConfig, Model};
+use lib_simulation::{SimulationConfig, IndividualConfig, Model, Encoding, Init, Strategy, ga};
 use std::f32::consts::PI;
 
 fn setup_configs() -> (SimulationConfig, IndividualConfig, IndividualConfig){
@@ -9,11 +9,15 @@ fn setup_configs() -> (SimulationConfig, IndividualConfig, IndividualConfig){
         nchasers: 0,
         nplants: 30,
         nworlds: 1000,
-        safe_evolve: true,
+        evolution: ga::EvolutionPolicy::half_elitist(),
         respawn_plants: false,
         mutation_probability: 0.005,
         mutation_magnitude: 0.3,
-        parallelized: true
+        parallelized: true,
+        selection: ga::Selection::RouletteWheel(ga::FitnessScaling::Raw),
+        crossover: ga::Crossover::Uniform,
+        mutation: ga::Mutation::Gaussian,
+        mutation_schedule: ga::MutationSchedule::default(),
     };
     let hervor_conf =IndividualConfig {
         speed_min: 0.001,
@@ -23,7 +27,15 @@ fn setup_configs() -> (SimulationConfig, IndividualConfig, IndividualConfig){
         fov_angle: PI + PI / 4.0,
         fov_range: 0.25,
         eye_cells: 9,
-        training_model: Model::CLOSEST
+        training_model: Model::CLOSEST,
+        recurrent: false,
+        plastic: false,
+        set_encoder: false,
+        encoding: Encoding::Direct,
+        init: Init::Uniform(1.0),
+        zero_bias: false,
+        strategy: Strategy::Genetic,
+        novelty: None,
     }; 
     let chaser_conf = IndividualConfig {
         speed_min: 0.001,
@@ -33,7 +45,15 @@ fn setup_configs() -> (SimulationConfig, IndividualConfig, IndividualConfig){
         fov_angle: PI + PI / 4.0,
         fov_range: 0.25,
         eye_cells: 9,
-        training_model: Model::CLOSEST
+        training_model: Model::CLOSEST,
+        recurrent: false,
+        plastic: false,
+        set_encoder: false,
+        encoding: Encoding::Direct,
+        init: Init::Uniform(1.0),
+        zero_bias: false,
+        strategy: Strategy::Genetic,
+        novelty: None,
     };
 
     (sim_conf, hervor_conf, chaser_conf)
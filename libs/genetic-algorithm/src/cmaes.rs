@@ -0,0 +1,181 @@
+use crate::*;
+use nalgebra::{DMatrix, DVector};
+
+/// Covariance matrix adaptation evolution strategy, see Hansen's "The CMA Evolution Strategy: A Tutorial".
+/// Every generation the best half of the population moves the mean, and the way they were spread
+/// shapes the covariance the next population is drawn from. The population size is whatever `evolve` gets.
+/// Keeps an n x n covariance matrix of the chromosome length n, so it suits small brains only
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CmaEs {
+    initial_sigma: f64,
+    //None until the first population comes in
+    mean: Option<DVector<f64>>,
+    sigma: f64,
+    covariance: DMatrix<f64>,
+    path_sigma: DVector<f64>,
+    path_covariance: DVector<f64>,
+    generation: usize,
+    //decomposition of the covariance, recomputed every few generations and after loading
+    #[serde(skip)]
+    eigen: Option<Eigen>,
+}
+
+#[derive(Clone, Debug)]
+struct Eigen {
+    basis: DMatrix<f64>,
+    //square roots of the eigenvalues
+    scales: DVector<f64>,
+    generation: usize,
+}
+
+//strategy parameters, they only depend on the chromosome length and the population size
+struct Params {
+    parents: usize,
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    chi_n: f64,
+    eigen_gap: usize,
+}
+
+impl Params {
+    fn new(n: usize, population: usize) -> Self {
+        let nf = n as f64;
+        let parents = (population / 2).max(1);
+        let raw: Vec<f64> = (0..parents).map(|i| (parents as f64 + 0.5).ln() - ((i + 1) as f64).ln()).collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / total).collect();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (nf + mu_eff + 5.0);
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / nf) / (nf + 4.0 + 2.0 * mu_eff / nf);
+        let c_1 = 2.0 / ((nf + 1.3).powi(2) + mu_eff);
+        let c_mu = (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((nf + 2.0).powi(2) + mu_eff));
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+        //the decomposition is O(n^3), the tutorial's lazy update keeps it below the cost of the rest
+        let eigen_gap = ((population as f64 / ((c_1 + c_mu) * nf * 10.0)) as usize).max(1);
+
+        Self { parents, weights, mu_eff, c_sigma, d_sigma, c_c, c_1, c_mu, chi_n, eigen_gap }
+    }
+}
+
+impl CmaEs {
+    /// `sigma` is the initial step size, about a quarter of the range good weights are expected in
+    pub fn new(sigma: f32) -> Self {
+        assert!(sigma > 0.0);
+
+        Self {
+            initial_sigma: sigma as f64,
+            mean: None,
+            sigma: sigma as f64,
+            covariance: DMatrix::zeros(0, 0),
+            path_sigma: DVector::zeros(0),
+            path_covariance: DVector::zeros(0),
+            generation: 0,
+            eigen: None,
+        }
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.sigma as f32
+    }
+
+    pub fn dimension(&self) -> Option<usize> {
+        self.mean.as_ref().map(|mean| mean.len())
+    }
+
+    //starts over around the weighted mean of the given parents
+    fn restart(&mut self, mean: DVector<f64>) {
+        let n = mean.len();
+        self.mean = Some(mean);
+        self.sigma = self.initial_sigma;
+        self.covariance = DMatrix::identity(n, n);
+        self.path_sigma = DVector::zeros(n);
+        self.path_covariance = DVector::zeros(n);
+        self.generation = 0;
+        self.eigen = None;
+    }
+
+    fn eigen(&mut self, gap: usize) -> &Eigen {
+        let stale = self.eigen.as_ref().is_none_or(|eigen| self.generation >= eigen.generation + gap);
+        if stale {
+            let symmetric = (&self.covariance + self.covariance.transpose()) * 0.5;
+            let decomposition = symmetric.symmetric_eigen();
+            self.eigen = Some(Eigen {
+                basis: decomposition.eigenvectors,
+                scales: decomposition.eigenvalues.map(|value| value.max(1e-20).sqrt()),
+                generation: self.generation,
+            });
+        }
+        self.eigen.as_ref().unwrap()
+    }
+
+    fn update(&mut self, params: &Params, parents: &[DVector<f64>]) {
+        let n = self.covariance.nrows() as f64;
+        let mean = self.mean.clone().unwrap();
+        let steps: Vec<DVector<f64>> = parents.iter().map(|x| (x - &mean) / self.sigma).collect();
+        let step = steps.iter().zip(&params.weights).fold(DVector::zeros(mean.len()), |sum, (y, w)| sum + y * *w);
+        self.mean = Some(&mean + &step * self.sigma);
+
+        //C^-1/2 * step, with C = B D^2 B^T
+        let eigen = self.eigen(params.eigen_gap);
+        let whitened = &eigen.basis * (eigen.basis.transpose() * &step).component_div(&eigen.scales);
+
+        self.path_sigma = &self.path_sigma * (1.0 - params.c_sigma) + whitened * (params.c_sigma * (2.0 - params.c_sigma) * params.mu_eff).sqrt();
+        let progress = self.path_sigma.norm() / (1.0 - (1.0 - params.c_sigma).powi(2 * (self.generation as i32 + 1))).sqrt();
+        let h_sigma = if progress < (1.4 + 2.0 / (n + 1.0)) * params.chi_n { 1.0 } else { 0.0 };
+        self.path_covariance = &self.path_covariance * (1.0 - params.c_c) + &step * (h_sigma * (params.c_c * (2.0 - params.c_c) * params.mu_eff).sqrt());
+
+        let rank_one = &self.path_covariance * self.path_covariance.transpose() + &self.covariance * ((1.0 - h_sigma) * params.c_c * (2.0 - params.c_c));
+        let spread = DMatrix::from_columns(&steps);
+        let weighted = DMatrix::from_columns(&steps.iter().zip(&params.weights).map(|(y, w)| y * *w).collect::<Vec<_>>());
+        let rank_mu = weighted * spread.transpose();
+        self.covariance = &self.covariance * (1.0 - params.c_1 - params.c_mu) + rank_one * params.c_1 + rank_mu * params.c_mu;
+
+        self.sigma *= ((params.c_sigma / params.d_sigma) * (self.path_sigma.norm() / params.chi_n - 1.0)).exp();
+        self.generation += 1;
+    }
+
+    fn sample(&mut self, rng: &mut dyn RngCore, gap: usize) -> Chromosome {
+        let sigma = self.sigma;
+        let n = self.covariance.nrows();
+        let noise = DVector::from_fn(n, |_, _| rng.sample::<f64, _>(StandardNormal));
+        let eigen = self.eigen(gap);
+        let offset = &eigen.basis * eigen.scales.component_mul(&noise) * sigma;
+        let x = self.mean.as_ref().unwrap() + offset;
+
+        x.iter().map(|&gene| gene as f32).collect()
+    }
+}
+
+impl<I> Optimizer<I> for CmaEs where I: Individual {
+    /// Elites and parent pools don't apply, the best half always makes the next mean
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: Vec<I>, _policy: &EvolutionPolicy) -> (Vec<I>, Statistics) {
+        assert!(!population.is_empty());
+
+        let stats = Statistics::new(&population);
+        let n = population[0].chromosome().len();
+        let params = Params::new(n, population.len());
+
+        let mut order: Vec<&I> = population.iter().collect();
+        order.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+        let parents: Vec<DVector<f64>> = order[..params.parents].iter()
+            .map(|individual| DVector::from_iterator(n, individual.chromosome().iter().map(|&gene| gene as f64)))
+            .collect();
+
+        if self.dimension() == Some(n) {
+            self.update(&params, &parents);
+        } else {
+            let mean = parents.iter().zip(&params.weights).fold(DVector::zeros(n), |sum, (x, w)| sum + x * *w);
+            self.restart(mean);
+        }
+
+        let children = (0..population.len()).map(|_| I::create(self.sample(rng, params.eigen_gap))).collect();
+        (children, stats.with_mutation_rates(1.0, self.sigma()))
+    }
+}
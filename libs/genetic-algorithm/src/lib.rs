@@ -1,86 +1,514 @@
 use rand::RngCore;
 use rand::Rng;
 use rand::seq::SliceRandom;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand_distr::{Cauchy, StandardNormal};
 use lib_individual::*;
 use lib_statistics::*;
+use serde::{Serialize, Deserialize};
+#[cfg(test)]
+use rand::SeedableRng;
+#[cfg(test)]
+use rand_chacha::ChaCha8Rng;
 
-//ooga booga do this: https://setu677.medium.com/how-to-perform-roulette-wheel-and-rank-based-selection-in-a-genetic-algorithm-d0829a37a189
+mod schedule;
+mod cmaes;
+mod openai_es;
+mod novelty;
+mod map_elites;
+mod nsga2;
+
+pub use schedule::{Schedule, StagnationBoost, MutationSchedule, MutationScheduler};
+pub use cmaes::CmaEs;
+pub use openai_es::OpenAiEs;
+pub use novelty::{NoveltySearch, NoveltyArchive};
+pub use map_elites::{Dimension, MapElites};
+pub use nsga2::{non_dominated_sort, crowding_distance, Nsga2};
+
+//https://setu677.medium.com/how-to-perform-roulette-wheel-and-rank-based-selection-in-a-genetic-algorithm-d0829a37a189
 pub trait SelectionMethod {
     fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I where I: Individual;
+
+    /// `count` picks from the same population, methods that weigh the whole population do that once instead of for every pick
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I> where I: Individual {
+        (0..count).map(|_| self.select(rng, population)).collect()
+    }
+}
+
+/// How raw fitness values are turned into roulette wheel slices
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum FitnessScaling {
+    /// Slices are the fitness values themselves, falls back to windowing if any of them is negative
+    #[default]
+    Raw,
+    /// Slices are moved and stretched so the best individual gets `c` times the average slice (usually 1.2 to 2.0),
+    /// squeezed further if the worst one would go below zero
+    Linear(f32),
+    /// Slices are `fitness - (mean - c * std_dev)`, individuals more than `c` deviations below the mean get nothing
+    SigmaTruncation(f32),
+    /// Slices are `fitness - min`, only the difference to the worst individual counts
+    Windowing,
+    /// Slices are `exp(fitness / temperature)`, higher temperatures flatten the wheel
+    Boltzmann(f32),
 }
 
-#[derive(Default)]
-pub struct RouletteWheelSelection;
+impl FitnessScaling {
+    pub fn scale(&self, fitness: &[f32]) -> Vec<f32> {
+        if fitness.is_empty() {
+            return Vec::new();
+        }
+        let n = fitness.len() as f32;
+        let min = fitness.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = fitness.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let mean = fitness.iter().sum::<f32>() / n;
+
+        match *self {
+            FitnessScaling::Raw if min >= 0.0 => fitness.to_vec(),
+            FitnessScaling::Raw | FitnessScaling::Windowing => fitness.iter().map(|f| f - min).collect(),
+            FitnessScaling::Linear(c) => {
+                //shifted first so negative fitness works too, the mean is kept where it is
+                let offset = min.min(0.0);
+                let (min, max, mean) = (min - offset, max - offset, mean - offset);
+                if max - mean <= f32::EPSILON * max {
+                    return fitness.iter().map(|f| f - offset).collect();
+                }
+                let (a, b) = if min > (c * mean - max) / (c - 1.0) {
+                    ((c - 1.0) * mean / (max - mean), mean * (max - c * mean) / (max - mean))
+                }
+                else {
+                    (mean / (mean - min), -min * mean / (mean - min))
+                };
+                fitness.iter().map(|f| (a * (f - offset) + b).max(0.0)).collect()
+            },
+            FitnessScaling::SigmaTruncation(c) => {
+                let std_dev = (fitness.iter().map(|f| (f - mean).powi(2)).sum::<f32>() / n).sqrt();
+                fitness.iter().map(|f| (f - (mean - c * std_dev)).max(0.0)).collect()
+            },
+            //relative to the best, so large fitness values don't overflow
+            FitnessScaling::Boltzmann(temperature) => fitness.iter().map(|f| ((f - max) / temperature).exp()).collect(),
+        }
+    }
+}
+
+/// Picks individuals proportionally to their scaled fitness.
+/// If nobody gets a slice (all tied after windowing, all zero, ...) the pick is uniform instead.
+#[derive(Clone, Debug, Default)]
+pub struct RouletteWheelSelection {
+    scaling: FitnessScaling,
+}
 
 impl RouletteWheelSelection {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_scaling(scaling: FitnessScaling) -> Self {
+        Self::try_with_scaling(scaling).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_with_scaling(scaling: FitnessScaling) -> Result<Self, String> {
+        let valid = match scaling {
+            FitnessScaling::Linear(c) => c > 1.0,
+            FitnessScaling::SigmaTruncation(c) => c >= 0.0,
+            FitnessScaling::Boltzmann(temperature) => temperature > 0.0,
+            FitnessScaling::Raw | FitnessScaling::Windowing => true,
+        };
+        if !valid {
+            return Err(format!("invalid fitness scaling {scaling:?}"));
+        }
+
+        Ok(Self { scaling })
+    }
+
+    fn wheel<I>(&self, population: &[I]) -> Option<WeightedIndex<f32>> where I: Individual {
+        let fitness: Vec<f32> = population.iter().map(|individual| individual.fitness()).collect();
+        WeightedIndex::new(self.scaling.scale(&fitness)).ok()
     }
 }
 
 impl SelectionMethod for RouletteWheelSelection {
     fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I 
     where I: Individual {
-        population
-        .choose_weighted(rng, |individual| individual.fitness())
-        .expect("got an empty population")
+        assert!(!population.is_empty(), "got an empty population");
+
+        match self.wheel(population) {
+            Some(index) => &population[index.sample(rng)],
+            None => population.choose(rng).unwrap(),
+        }
+    }
+
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I>
+    where I: Individual {
+        assert!(!population.is_empty(), "got an empty population");
+
+        let wheel = self.wheel(population);
+        (0..count).map(|_| match &wheel {
+            Some(index) => &population[index.sample(rng)],
+            None => population.choose(rng).unwrap(),
+        }).collect()
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RankPressure {
+    /// Selection weights fall linearly with the rank, the value is the expected number of times the best
+    /// individual is picked per population-sized batch of selections:
+    /// - 1.0 = every rank is equally likely
+    /// - 2.0 = the worst individual is never picked
+    Linear(f32),
+    /// Every rank is `base` times as likely as the one above it, lower means stronger pressure, must be in (0.0, 1.0]
+    Exponential(f32),
+}
+
+/// Selects by fitness order only, so tied, negative or heavily skewed fitness values don't matter.
+/// Individuals with equal fitness share the average of their ranks.
+#[derive(Clone, Debug)]
+pub struct RankSelection {
+    pressure: RankPressure,
+}
+
+impl RankSelection {
+    pub fn new(pressure: RankPressure) -> Self {
+        Self::try_new(pressure).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(pressure: RankPressure) -> Result<Self, String> {
+        let valid = match pressure {
+            RankPressure::Linear(pressure) => (1.0..=2.0).contains(&pressure),
+            RankPressure::Exponential(base) => base > 0.0 && base <= 1.0,
+        };
+        if !valid {
+            return Err(format!("invalid rank pressure {pressure:?}"));
+        }
+
+        Ok(Self { pressure })
+    }
+
+    //rank 0.0 is the worst individual, n - 1 the best
+    fn weight(&self, rank: f32, n: usize) -> f32 {
+        if n < 2 {
+            return 1.0;
+        }
+        match self.pressure {
+            RankPressure::Linear(pressure) => (2.0 - pressure) + 2.0 * (pressure - 1.0) * rank / (n - 1) as f32,
+            RankPressure::Exponential(base) => base.powf((n - 1) as f32 - rank),
+        }
+    }
+}
+
+impl Default for RankSelection {
+    fn default() -> Self {
+        Self::new(RankPressure::Linear(1.5))
+    }
+}
+
+//rank of every individual in population order, ties get the average of the ranks they span
+fn ranks<I>(population: &[I]) -> Vec<f32> where I: Individual {
+    let mut order: Vec<usize> = (0..population.len()).collect();
+    order.sort_by(|&a, &b| population[a].fitness().total_cmp(&population[b].fitness()));
+
+    let mut ranks = vec![0.0; population.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let fitness = population[order[start]].fitness();
+        let end = start + order[start..].iter().take_while(|&&idx| population[idx].fitness() == fitness).count().max(1);
+        let rank = (start + end - 1) as f32 / 2.0;
+        for &idx in &order[start..end] {
+            ranks[idx] = rank;
+        }
+        start = end;
+    }
+
+    ranks
+}
+
+impl SelectionMethod for RankSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where I: Individual {
+        self.select_many(rng, population, 1)[0]
+    }
+
+    //the population is ranked once for all of the picks
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I>
+    where I: Individual {
+        assert!(!population.is_empty(), "got an empty population");
+
+        let weights: Vec<f32> = ranks(population).into_iter().map(|rank| self.weight(rank, population.len())).collect();
+        let index = WeightedIndex::new(&weights).expect("rank weights are positive");
+        (0..count).map(|_| &population[index.sample(rng)]).collect()
+    }
+}
+
+/// Picks `size` random individuals and lets them compete, the fittest wins with `win_probability`,
+/// otherwise the second fittest with `win_probability` and so on. The last one takes whatever is left.
+#[derive(Clone, Debug)]
+pub struct TournamentSelection {
+    size: usize,
+    win_probability: f32,
+}
+
+impl TournamentSelection {
+    pub fn new(size: usize, win_probability: f32) -> Self {
+        Self::try_new(size, win_probability).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(size: usize, win_probability: f32) -> Result<Self, String> {
+        if size == 0 {
+            return Err("tournaments need at least one contestant".to_string());
+        }
+        if !(0.0..=1.0).contains(&win_probability) {
+            return Err(format!("invalid tournament win probability {win_probability}"));
+        }
+
+        Ok(Self { size, win_probability })
+    }
+}
+
+impl Default for TournamentSelection {
+    fn default() -> Self {
+        Self::new(3, 1.0)
+    }
+}
+
+impl SelectionMethod for TournamentSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where I: Individual {
+        assert!(!population.is_empty(), "got an empty population");
+
+        //drawn with replacement, so tournaments larger than the population are fine
+        let mut contestants: Vec<&I> = (0..self.size).map(|_| &population[rng.gen_range(0..population.len())]).collect();
+        contestants.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        let last = contestants.len() - 1;
+        for (place, contestant) in contestants.iter().enumerate() {
+            if place == last || rng.gen_bool(self.win_probability as _) {
+                return contestant;
+            }
+        }
+        unreachable!()
+    }
+}
+
+/// Every selection method by its parameters, so it can be picked from a config file
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Selection {
+    RouletteWheel(FitnessScaling),
+    Rank(RankPressure),
+    Tournament { size: usize, win_probability: f32 },
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Selection::RouletteWheel(FitnessScaling::Raw)
+    }
+}
+
+impl Selection {
+    /// Checks the parameters once, the GA keeps the built method
+    pub fn build(&self) -> Result<Selector, String> {
+        Ok(match *self {
+            Selection::RouletteWheel(scaling) => Selector::RouletteWheel(RouletteWheelSelection::try_with_scaling(scaling)?),
+            Selection::Rank(pressure) => Selector::Rank(RankSelection::try_new(pressure)?),
+            Selection::Tournament { size, win_probability } => Selector::Tournament(TournamentSelection::try_new(size, win_probability)?),
+        })
+    }
+}
+
+/// A `Selection` that passed its checks
+#[derive(Clone, Debug)]
+pub enum Selector {
+    RouletteWheel(RouletteWheelSelection),
+    Rank(RankSelection),
+    Tournament(TournamentSelection),
+}
+
+impl SelectionMethod for Selector {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where I: Individual {
+        match self {
+            Selector::RouletteWheel(method) => method.select(rng, population),
+            Selector::Rank(method) => method.select(rng, population),
+            Selector::Tournament(method) => method.select(rng, population),
+        }
+    }
+
+    fn select_many<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], count: usize) -> Vec<&'a I>
+    where I: Individual {
+        match self {
+            Selector::RouletteWheel(method) => method.select_many(rng, population, count),
+            Selector::Rank(method) => method.select_many(rng, population, count),
+            Selector::Tournament(method) => method.select_many(rng, population, count),
+        }
+    }
+}
+
+/// Anything that turns an evaluated population into the next one of the same size
+pub trait Optimizer<I> where I: Individual {
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: Vec<I>, policy: &EvolutionPolicy) -> (Vec<I>, Statistics);
+
+    /// For optimizers that care about how the individuals behaved and not just how fit they were,
+    /// `behaviours` lines up with the population. The others ignore it
+    fn evolve_described(&mut self, rng: &mut dyn RngCore, population: Vec<I>, _behaviours: &[Vec<f32>], policy: &EvolutionPolicy) -> (Vec<I>, Statistics) {
+        self.evolve(rng, population, policy)
+    }
+
+    /// Lets schedules carry on from where a reloaded run left off
+    fn set_generation(&mut self, _generation: usize) {}
+}
+
 pub struct GeneticAlgorithm<S> {
     selection_method: S,
     crossover_method: Box<dyn CrossoverMethod>,
     mutation_method: Box<dyn MutationMethod>,
+    scheduler: Option<MutationScheduler>,
 }
 
 impl<S> GeneticAlgorithm<S> where S: SelectionMethod{
     pub fn new(selection_method: S, crossover_method: impl CrossoverMethod + 'static, mutation_method: impl MutationMethod + 'static) -> Self {
-        Self { selection_method, crossover_method: Box::new(crossover_method), mutation_method: Box::new(mutation_method)}
+        Self { selection_method, crossover_method: Box::new(crossover_method), mutation_method: Box::new(mutation_method), scheduler: None }
     }
 
-    //unoptimized AF function
-    fn safe_evolve<I>(&self, rng: &mut dyn RngCore, mut population: Vec<I>) -> (Vec<I>, Statistics) where I: Individual {
-        let stats = Statistics::new(&population);
-        population.sort_by(|x, y| x.fitness().total_cmp(&y.fitness()));
-        let mut top_50:Vec<I> = population[(population.len() / 2)..population.len()].iter().map(|ind| I::create_fit(ind.chromosome().clone(), ind.fitness())).collect();
-
-        let mut addon_population:Vec<I> = (0..(population.len() - top_50.len()))
-        .map(|_| {
-            let parent1 = self.selection_method.select(rng, &top_50).chromosome();
-            let parent2 = self.selection_method.select(rng, &top_50).chromosome();
-            let mut child = self.crossover_method.crossover(rng, parent1, parent2);
-            self.mutation_method.mutate(rng, &mut child);
-            
-            return I::create(child);
-        }).collect();
-        top_50.append(&mut addon_population);
+    /// Adjusts the mutation rates before every generation, they are reported in the statistics `evolve` returns
+    pub fn with_scheduler(mut self, scheduler: MutationScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
 
-        (top_50, stats)
+    pub fn scheduler_mut(&mut self) -> Option<&mut MutationScheduler> {
+        self.scheduler.as_mut()
     }
 
-    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: Vec<I>, safe_evolve: bool) -> (Vec<I>, Statistics) where I: Individual{
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, mut population: Vec<I>, policy: &EvolutionPolicy) -> (Vec<I>, Statistics) where I: Individual{
         assert!(!population.is_empty());
 
-        if safe_evolve {
-            return self.safe_evolve(rng, population);
+        let mut stats = Statistics::new(&population);
+        if let Some(scheduler) = &mut self.scheduler {
+            let (chance, size) = scheduler.next(stats.max_fitness());
+            self.mutation_method.set_rates(chance, size);
+            stats = stats.with_mutation_rates(chance, size);
+        }
+        let (elites, parents) = policy.sizes(population.len());
+
+        //only the best `max(elites, parents)` get partitioned to the front, nobody is fully sorted
+        let by_fitness = |a: &I, b: &I| b.fitness().total_cmp(&a.fitness());
+        let best = elites.max(parents);
+        if best < population.len() {
+            population.select_nth_unstable_by(best, by_fitness);
+        }
+        let fewer = elites.min(parents);
+        if fewer > 0 && fewer < best {
+            population[..best].select_nth_unstable_by(fewer, by_fitness);
         }
 
-        let stats = Statistics::new(&population);
-        let new_population = (0..population.len())
-        .map(|_| {
-            let parent1 = self.selection_method.select(rng, &population).chromosome();
-            let parent2 = self.selection_method.select(rng, &population).chromosome();
+        let mut new_population: Vec<I> = population[..elites].iter()
+        .map(|elite| if policy.reevaluate {
+            I::create(elite.chromosome().clone())
+        } else {
+            I::create_fit(elite.chromosome().clone(), elite.fitness())
+        })
+        .collect();
+
+        let pool = &population[..parents];
+        let picks = self.selection_method.select_many(rng, pool, 2 * (population.len() - elites));
+        new_population.extend(picks.chunks(2)
+        .map(|pair| {
+            let (parent1, parent2) = (pair[0].chromosome(), pair[1].chromosome());
             let mut child = self.crossover_method.crossover(rng, parent1, parent2);
+            child.set_step_sizes(inherit_step_sizes(parent1, parent2));
             self.mutation_method.mutate(rng, &mut child);
             
-            return I::create(child);
-        })
-        .collect();
+            I::create(child)
+        }));
 
         (new_population, stats)
     }
 }
 
+//children get the mean of their parents' step sizes, or those of the only parent that has them
+fn inherit_step_sizes(parent_a: &Chromosome, parent_b: &Chromosome) -> Vec<f32> {
+    match (parent_a.step_sizes(), parent_b.step_sizes()) {
+        (a, b) if a.len() == b.len() => a.iter().zip(b).map(|(a, b)| (a + b) / 2.0).collect(),
+        (a, []) => a.to_vec(),
+        (_, b) => b.to_vec(),
+    }
+}
+
+impl<S, I> Optimizer<I> for GeneticAlgorithm<S> where S: SelectionMethod, I: Individual {
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: Vec<I>, policy: &EvolutionPolicy) -> (Vec<I>, Statistics) {
+        GeneticAlgorithm::evolve(self, rng, population, policy)
+    }
+
+    fn set_generation(&mut self, generation: usize) {
+        if let Some(scheduler) = &mut self.scheduler {
+            scheduler.set_generation(generation);
+        }
+    }
+}
+
+/// How many individuals are taken over from one generation to the next
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Share {
+    Count(usize),
+    /// Rounded share of the population, must be in [0.0, 1.0]
+    Fraction(f32),
+}
+
+impl Share {
+    pub fn of(&self, population: usize) -> usize {
+        match *self {
+            Share::Count(count) => count.min(population),
+            Share::Fraction(fraction) => ((population as f32 * fraction).round() as usize).min(population),
+        }
+    }
+}
+
+/// What survives a generation and who gets to breed
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvolutionPolicy {
+    /// The best individuals, copied over unchanged
+    pub elites: Share,
+    /// The best individuals the selection method picks parents from, never fewer than one
+    pub parents: Share,
+    /// Elites start over with no fitness and are judged again, otherwise they keep it through `create_fit`
+    pub reevaluate: bool,
+}
+
+impl EvolutionPolicy {
+    /// Everybody is replaced by children of the whole population
+    pub fn generational() -> Self {
+        Self { elites: Share::Count(0), parents: Share::Fraction(1.0), reevaluate: true }
+    }
+
+    /// The better half survives and breeds the other half
+    pub fn half_elitist() -> Self {
+        Self { elites: Share::Fraction(0.5), parents: Share::Fraction(0.5), reevaluate: true }
+    }
+
+    /// Checks the fractions up front, `evolve` panics on policies that don't pass
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, share) in [("elites", self.elites), ("parents", self.parents)] {
+            if let Share::Fraction(fraction) = share {
+                if !(0.0..=1.0).contains(&fraction) {
+                    return Err(format!("{name} fraction {fraction} is outside of [0.0, 1.0]"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn sizes(&self, population: usize) -> (usize, usize) {
+        self.validate().unwrap_or_else(|err| panic!("{err}"));
+
+        (self.elites.of(population), self.parents.of(population).max(1))
+    }
+}
+
+impl Default for EvolutionPolicy {
+    fn default() -> Self {
+        Self::generational()
+    }
+}
+
 pub trait CrossoverMethod: Send + Sync {
     fn crossover(
         &self,
@@ -113,8 +541,147 @@ impl CrossoverMethod for UniformCrossover {
     }
 }
 
+/// Cuts both parents at `points` random places and takes the pieces from either parent in turn
+#[derive(Clone, Debug)]
+pub struct KPointCrossover {
+    points: usize,
+}
+
+impl KPointCrossover {
+    pub fn new(points: usize) -> Self {
+        assert!(points > 0);
+
+        Self { points }
+    }
+
+    pub fn single_point() -> Self {
+        Self::new(1)
+    }
+}
+
+impl CrossoverMethod for KPointCrossover {
+    fn crossover(&self, rng: &mut dyn RngCore, parent_a: &Chromosome, parent_b: &Chromosome) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        //cuts go between genes, never before the first or after the last one
+        let len = parent_a.len();
+        //distinct cuts, two at the same place would cancel out
+        let mut cuts: Vec<usize> = rand::seq::index::sample(rng, len.saturating_sub(1), self.points.min(len.saturating_sub(1)))
+            .into_iter().map(|cut| cut + 1).collect();
+        cuts.sort_unstable();
+
+        let mut cuts = cuts.into_iter().peekable();
+        let mut from_a = rng.gen_bool(0.5);
+        parent_a.iter().zip(parent_b.iter()).enumerate()
+            .map(|(idx, (&a, &b))| {
+                if cuts.next_if_eq(&idx).is_some() {
+                    from_a = !from_a;
+                }
+                if from_a { a } else { b }
+            })
+            .collect()
+    }
+}
+
+/// Every gene is drawn uniformly from the range spanned by both parents, widened by `alpha` times its length on each side.
+/// `alpha` 0.0 is a plain arithmetic crossover at a random point between the parents
+#[derive(Clone, Debug)]
+pub struct BlendCrossover {
+    alpha: f32,
+}
+
+impl BlendCrossover {
+    pub fn new(alpha: f32) -> Self {
+        assert!(alpha >= 0.0);
+
+        Self { alpha }
+    }
+}
+
+impl CrossoverMethod for BlendCrossover {
+    fn crossover(&self, rng: &mut dyn RngCore, parent_a: &Chromosome, parent_b: &Chromosome) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        parent_a.iter().zip(parent_b.iter())
+            .map(|(&a, &b)| {
+                let (low, high) = (a.min(b), a.max(b));
+                let margin = self.alpha * (high - low);
+                low - margin + rng.gen::<f32>() * (high - low + 2.0 * margin)
+            })
+            .collect()
+    }
+}
+
+/// Uniform crossover over whole blocks of genes, with the sizes from `Network::neuron_sizes` every neuron
+/// keeps its bias and incoming weights from the same parent. Genes after the last block are crossed one by one
+#[derive(Clone, Debug)]
+pub struct BlockCrossover {
+    blocks: Vec<usize>,
+}
+
+impl BlockCrossover {
+    pub fn new(blocks: Vec<usize>) -> Self {
+        Self { blocks }
+    }
+}
+
+impl CrossoverMethod for BlockCrossover {
+    fn crossover(&self, rng: &mut dyn RngCore, parent_a: &Chromosome, parent_b: &Chromosome) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let mut blocks = self.blocks.iter().copied().filter(|&size| size > 0);
+        let mut left = 0;
+        let mut from_a = true;
+        parent_a.iter().zip(parent_b.iter())
+            .map(|(&a, &b)| {
+                if left == 0 {
+                    left = blocks.next().unwrap_or(1);
+                    from_a = rng.gen_bool(0.5);
+                }
+                left -= 1;
+                if from_a { a } else { b }
+            })
+            .collect()
+    }
+}
+
+impl<C> CrossoverMethod for Box<C> where C: CrossoverMethod + ?Sized {
+    fn crossover(&self, rng: &mut dyn RngCore, parent_a: &Chromosome, parent_b: &Chromosome) -> Chromosome {
+        (**self).crossover(rng, parent_a, parent_b)
+    }
+}
+
+/// Every crossover method by its parameters, so it can be picked from a config file
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum Crossover {
+    #[default]
+    Uniform,
+    SinglePoint,
+    KPoint(usize),
+    /// BLX-alpha, 0.0 for arithmetic crossover
+    Blend(f32),
+    /// Swaps whole neurons, needs the chromosome layout
+    NeuronBlock,
+}
+
+impl Crossover {
+    /// `neurons` is the length of every neuron's genes in chromosome order, only `NeuronBlock` looks at it
+    pub fn build(self, neurons: Vec<usize>) -> Box<dyn CrossoverMethod> {
+        match self {
+            Crossover::Uniform => Box::new(UniformCrossover::new()),
+            Crossover::SinglePoint => Box::new(KPointCrossover::single_point()),
+            Crossover::KPoint(points) => Box::new(KPointCrossover::new(points)),
+            Crossover::Blend(alpha) => Box::new(BlendCrossover::new(alpha)),
+            Crossover::NeuronBlock => Box::new(BlockCrossover::new(neurons)),
+        }
+    }
+}
+
 pub trait MutationMethod: Send + Sync{
     fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome);
+
+    /// Called by schedules, methods without a chance or a size can ignore it
+    fn set_rates(&mut self, _chance: f32, _size: f32) {}
 }
 
 #[derive(Clone, Debug)]
@@ -124,9 +691,9 @@ pub struct GaussianMutation {
     /// - 1.0 = all genes will be touched
     chance: f32,
 
-    /// Magnitude of that change:
+    /// Standard deviation of that change:
     /// - 0.0 = touched genes will not be modified
-    /// - 3.0 = touched genes will be += or -= by at most 3.0
+    /// - 3.0 = touched genes will mostly move by less than 3.0
     coeff: f32,
 }
 
@@ -141,11 +708,560 @@ impl GaussianMutation {
 impl MutationMethod for GaussianMutation {
     fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
         for gene in child.iter_mut() {
-            let sign = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+            if rng.gen_bool(self.chance as _) {
+                *gene += self.coeff * rng.sample::<f32, _>(StandardNormal);
+            }
+        }
+    }
+
+    fn set_rates(&mut self, chance: f32, size: f32) {
+        self.chance = chance;
+        self.coeff = size;
+    }
+}
+
+/// Like `GaussianMutation` but heavy tailed, most changes are small and a few are huge
+pub struct CauchyMutation {
+    chance: f32,
+    /// Half of the touched genes move by less than this
+    scale: f32,
+}
+
+impl CauchyMutation {
+    pub fn new(chance: f32, scale: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(scale >= 0.0);
+
+        Self { chance, scale }
+    }
+}
+
+impl MutationMethod for CauchyMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        if self.scale == 0.0 {
+            return;
+        }
+        let cauchy = Cauchy::new(0.0, self.scale).unwrap();
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as _) {
+                *gene += cauchy.sample(rng);
+            }
+        }
+    }
+
+    fn set_rates(&mut self, chance: f32, size: f32) {
+        self.chance = chance;
+        self.scale = size;
+    }
+}
+
+/// Evolution strategies style mutation, every gene has its own step size carried along in the chromosome.
+/// Step sizes of touched genes change log-normally before the gene moves by a normal sample of that size,
+/// so the ones that lead to fitter children get inherited
+pub struct SelfAdaptiveMutation {
+    chance: f32,
+    /// Step size of chromosomes that don't carry any yet, e.g. in generation zero
+    initial_step: f32,
+    /// Step sizes never shrink below this, otherwise genes can freeze for good
+    min_step: f32,
+}
 
+impl SelfAdaptiveMutation {
+    pub fn new(chance: f32, initial_step: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(initial_step > 0.0);
+
+        Self { chance, initial_step, min_step: 1e-4 }
+    }
+}
+
+impl MutationMethod for SelfAdaptiveMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        let mut step_sizes = child.take_step_sizes();
+        if step_sizes.len() != child.len() {
+            step_sizes = vec![self.initial_step; child.len()];
+        }
+
+        //learning rates from Schwefel, one draw shared by the whole chromosome and one per gene
+        let n = child.len().max(1) as f32;
+        let tau_global = 1.0 / (2.0 * n).sqrt();
+        let tau_local = 1.0 / (2.0 * n.sqrt()).sqrt();
+        let global = tau_global * rng.sample::<f32, _>(StandardNormal);
+
+        for (gene, step) in child.iter_mut().zip(step_sizes.iter_mut()) {
             if rng.gen_bool(self.chance as _) {
-                *gene += sign * self.coeff * rng.gen::<f32>();
+                *step = (*step * (global + tau_local * rng.sample::<f32, _>(StandardNormal)).exp()).max(self.min_step);
+                *gene += *step * rng.sample::<f32, _>(StandardNormal);
             }
         }
+        child.set_step_sizes(step_sizes);
+    }
+
+    //only new chromosomes start from the size, the others keep their own step sizes
+    fn set_rates(&mut self, chance: f32, size: f32) {
+        self.chance = chance;
+        self.initial_step = size;
+    }
+}
+
+impl<M> MutationMethod for Box<M> where M: MutationMethod + ?Sized {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        (**self).mutate(rng, child)
+    }
+
+    fn set_rates(&mut self, chance: f32, size: f32) {
+        (**self).set_rates(chance, size)
+    }
+}
+
+/// Every mutation method by its distribution, the chance and size come from the simulation config
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum Mutation {
+    #[default]
+    Gaussian,
+    Cauchy,
+    /// The size is the initial step size
+    SelfAdaptive,
+}
+
+impl Mutation {
+    pub fn build(self, chance: f32, size: f32) -> Box<dyn MutationMethod> {
+        match self {
+            Mutation::Gaussian => Box::new(GaussianMutation::new(chance, size)),
+            Mutation::Cauchy => Box::new(CauchyMutation::new(chance, size)),
+            Mutation::SelfAdaptive => Box::new(SelfAdaptiveMutation::new(chance, size)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Clone)]
+struct TestIndividual {
+    fitness: f32,
+    chromosome: Chromosome,
+}
+
+#[cfg(test)]
+impl Individual for TestIndividual {
+    fn fitness(&self) -> f32 {
+        self.fitness
+    }
+
+    fn chromosome(&self) -> &Chromosome {
+        &self.chromosome
+    }
+
+    fn create(chromosome: Chromosome) -> Self {
+        Self { fitness: 0.0, chromosome }
+    }
+
+    fn create_fit(chromosome: Chromosome, fitness: f32) -> Self {
+        Self { fitness, chromosome }
+    }
+}
+
+#[cfg(test)]
+fn selection_counts(method: &impl SelectionMethod, fitness: &[f32]) -> Vec<usize> {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    //the only gene is the individual's index
+    let population: Vec<TestIndividual> = fitness.iter().enumerate().map(|(idx, &f)| TestIndividual::create_fit(Chromosome::new(vec![idx as f32]), f)).collect();
+
+    let mut counts = vec![0; population.len()];
+    for _ in 0..10_000 {
+        let selected = method.select(&mut rng, &population);
+        counts[selected.chromosome()[0] as usize] += 1;
+    }
+    counts
+}
+
+#[test]
+fn test_rank_selection() {
+    //skewed and negative fitness, only the order counts
+    let counts = selection_counts(&RankSelection::new(RankPressure::Linear(2.0)), &[-5.0, 0.0, 0.1, 1000.0]);
+    assert_eq!(counts[0], 0);
+    assert!(counts.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let counts = selection_counts(&RankSelection::new(RankPressure::Exponential(0.5)), &[3.0, 0.0, 1.0, 2.0]);
+    assert!(counts[3] > counts[2] && counts[2] > counts[1] && counts[0] > counts[3]);
+
+    //all tied is a uniform pick, roulette wheel would panic on all zeros
+    let counts = selection_counts(&RankSelection::default(), &[0.0; 4]);
+    assert!(counts.iter().all(|&count| (2000..3000).contains(&count)));
+
+    //a batch ranks the population once and picks with the same weights
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let population: Vec<TestIndividual> = (0..4).map(|idx| TestIndividual::create_fit(Chromosome::new(vec![idx as f32]), idx as f32)).collect();
+    let mut counts = vec![0; population.len()];
+    for selected in RankSelection::new(RankPressure::Linear(2.0)).select_many(&mut rng, &population, 10_000) {
+        counts[selected.chromosome()[0] as usize] += 1;
+    }
+    assert_eq!(counts[0], 0);
+    assert!(counts.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_selection_build() {
+    assert!(Selection::default().build().is_ok());
+    assert!(Selection::Rank(RankPressure::Linear(1.0)).build().is_ok());
+    assert!(Selection::RouletteWheel(FitnessScaling::Linear(1.0)).build().is_err());
+    assert!(Selection::Rank(RankPressure::Linear(2.5)).build().is_err());
+    assert!(Selection::Rank(RankPressure::Exponential(0.0)).build().is_err());
+    assert!(Selection::Tournament { size: 0, win_probability: 1.0 }.build().is_err());
+    assert!(Selection::Tournament { size: 3, win_probability: 1.5 }.build().is_err());
+}
+
+#[test]
+fn test_evolution_policy_validate() {
+    assert!(EvolutionPolicy::generational().validate().is_ok());
+    assert!(EvolutionPolicy::half_elitist().validate().is_ok());
+    assert!(EvolutionPolicy { elites: Share::Count(100), parents: Share::Fraction(0.0), reevaluate: false }.validate().is_ok());
+    assert!(EvolutionPolicy { elites: Share::Fraction(1.5), parents: Share::Fraction(1.0), reevaluate: true }.validate().is_err());
+    assert!(EvolutionPolicy { elites: Share::Count(0), parents: Share::Fraction(-0.1), reevaluate: true }.validate().is_err());
+    assert!(EvolutionPolicy { elites: Share::Fraction(f32::NAN), parents: Share::Fraction(1.0), reevaluate: true }.validate().is_err());
+}
+
+#[test]
+fn test_tournament_selection() {
+    //deterministic tournaments of the whole population always pick the best
+    let counts = selection_counts(&TournamentSelection::new(64, 1.0), &[1.0, 4.0, 2.0, 3.0]);
+    assert_eq!(counts, vec![0, 10_000, 0, 0]);
+
+    //a single contestant is a uniform pick whatever the fitness
+    let counts = selection_counts(&Selection::Tournament { size: 1, win_probability: 1.0 }.build().unwrap(), &[1.0, 100.0, 2.0, 3.0]);
+    assert!(counts.iter().all(|&count| (2000..3000).contains(&count)));
+
+    //weaker pressure spreads the picks but keeps the order
+    let counts = selection_counts(&TournamentSelection::new(2, 0.75), &[1.0, 2.0, 3.0, 4.0]);
+    assert!(counts.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_fitness_scaling() {
+    let fitness = [1.0, 2.0, 3.0, 6.0];
+    assert_eq!(FitnessScaling::Raw.scale(&fitness), fitness);
+    assert_eq!(FitnessScaling::Windowing.scale(&fitness), [0.0, 1.0, 2.0, 5.0]);
+    assert_eq!(FitnessScaling::Raw.scale(&[-1.0, 1.0]), [0.0, 2.0]);
+
+    //the mean stays, the best gets c times the mean
+    let scaled = FitnessScaling::Linear(1.5).scale(&fitness);
+    assert!((scaled.iter().sum::<f32>() - 12.0).abs() < 1e-4);
+    assert!((scaled[3] - 4.5).abs() < 1e-4);
+    assert!(scaled.windows(2).all(|pair| pair[0] < pair[1]));
+    //too much pressure for the worst to stay positive squeezes it down to zero
+    let scaled = FitnessScaling::Linear(2.0).scale(&[0.0, 0.0, 0.0, 10.0]);
+    assert!(scaled.iter().all(|&f| f >= 0.0));
+
+    let scaled = FitnessScaling::SigmaTruncation(1.0).scale(&fitness);
+    assert_eq!(scaled[0], 0.0);
+    assert!(scaled[1] > 0.0);
+
+    let scaled = FitnessScaling::Boltzmann(1.0).scale(&[1000.0, 999.0]);
+    assert_eq!(scaled[0], 1.0);
+    assert!((scaled[1] - (-1.0f32).exp()).abs() < 1e-6);
+}
+
+#[test]
+fn test_roulette_fallback() {
+    //nobody scored, every individual is equally likely instead of a panic
+    let counts = selection_counts(&RouletteWheelSelection::new(), &[0.0; 4]);
+    assert!(counts.iter().all(|&count| (2000..3000).contains(&count)));
+
+    let counts = selection_counts(&Selection::RouletteWheel(FitnessScaling::Windowing).build().unwrap(), &[-5.0; 4]);
+    assert!(counts.iter().all(|&count| (2000..3000).contains(&count)));
+
+    //all equal fitness has no deviation, sigma truncation leaves every slice at zero
+    assert_eq!(FitnessScaling::SigmaTruncation(2.0).scale(&[3.0; 4]), vec![0.0; 4]);
+    let counts = selection_counts(&Selection::RouletteWheel(FitnessScaling::SigmaTruncation(2.0)).build().unwrap(), &[3.0; 4]);
+    assert!(counts.iter().all(|&count| (2000..3000).contains(&count)));
+
+    //the batch the GA draws falls back the same way
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let population: Vec<TestIndividual> = (0..4).map(|idx| TestIndividual::create_fit(Chromosome::new(vec![idx as f32]), 3.0)).collect();
+    let mut counts = vec![0; population.len()];
+    for selected in RouletteWheelSelection::with_scaling(FitnessScaling::SigmaTruncation(2.0)).select_many(&mut rng, &population, 10_000) {
+        counts[selected.chromosome()[0] as usize] += 1;
+    }
+    assert!(counts.iter().all(|&count| (2000..3000).contains(&count)));
+
+    let counts = selection_counts(&RouletteWheelSelection::new(), &[-1.0, 0.0, 1.0, 3.0]);
+    assert_eq!(counts[0], 0);
+    assert!(counts[1] < counts[2] && counts[2] < counts[3]);
+}
+
+#[test]
+fn test_evolution_policy() {
+    let mut ga = GeneticAlgorithm::new(RouletteWheelSelection::new(), UniformCrossover::new(), GaussianMutation::new(0.0, 0.0));
+    let population = || (0..10).map(|idx| TestIndividual::create_fit(Chromosome::new(vec![idx as f32]), idx as f32)).collect::<Vec<_>>();
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+    //the three best come first and keep their fitness, children only descend from the best five
+    let policy = EvolutionPolicy { elites: Share::Count(3), parents: Share::Fraction(0.5), reevaluate: false };
+    let (evolved, _) = ga.evolve(&mut rng, population(), &policy);
+    assert_eq!(evolved.len(), 10);
+    let mut elites: Vec<f32> = evolved[..3].iter().map(|ind| ind.fitness()).collect();
+    elites.sort_by(f32::total_cmp);
+    assert_eq!(elites, [7.0, 8.0, 9.0]);
+    assert!(evolved[3..].iter().all(|ind| ind.fitness() == 0.0 && ind.chromosome()[0] >= 5.0));
+
+    let policy = EvolutionPolicy { reevaluate: true, ..policy };
+    let (evolved, _) = ga.evolve(&mut rng, population(), &policy);
+    assert!(evolved.iter().all(|ind| ind.fitness() == 0.0));
+
+    //with nobody kept and everybody breeding, a population of clones stays one
+    let (evolved, _) = ga.evolve(&mut rng, vec![TestIndividual::create_fit(Chromosome::new(vec![1.0]), 1.0); 4], &EvolutionPolicy::generational());
+    assert!(evolved.iter().all(|ind| ind.chromosome()[0] == 1.0));
+}
+
+#[test]
+fn test_crossovers() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let parent_a = Chromosome::new(vec![0.0; 8]);
+    let parent_b = Chromosome::new(vec![1.0; 8]);
+    let switches = |child: &Chromosome| child.iter().zip(child.iter().skip(1)).filter(|(a, b)| a != b).count();
+
+    for _ in 0..100 {
+        let child = KPointCrossover::single_point().crossover(&mut rng, &parent_a, &parent_b);
+        assert_eq!(switches(&child), 1);
+        let child = KPointCrossover::new(3).crossover(&mut rng, &parent_a, &parent_b);
+        assert_eq!(switches(&child), 3);
+        //as many cuts as there are gaps switches at every gene
+        let child = KPointCrossover::new(7).crossover(&mut rng, &parent_a, &parent_b);
+        assert_eq!(switches(&child), 7);
+
+        let child = BlendCrossover::new(0.5).crossover(&mut rng, &parent_a, &parent_b);
+        assert!(child.iter().all(|&gene| (-0.5..=1.5).contains(&gene)));
+        let child = BlendCrossover::new(0.0).crossover(&mut rng, &parent_b, &parent_b);
+        assert!(child.iter().all(|&gene| gene == 1.0));
+
+        //blocks of 3 and 2 genes stay whole, the last gene is on its own
+        let child = BlockCrossover::new(vec![3, 2, 2]).crossover(&mut rng, &parent_a, &parent_b);
+        assert!(child[0] == child[1] && child[1] == child[2]);
+        assert!(child[3] == child[4] && child[5] == child[6]);
+    }
+}
+
+#[test]
+fn test_mutations() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let deltas = |method: &dyn MutationMethod, rng: &mut ChaCha8Rng| {
+        let mut child = Chromosome::new(vec![0.0; 10_000]);
+        method.mutate(rng, &mut child);
+        child.into_iter().collect::<Vec<f32>>()
+    };
+
+    //a true normal sample, about 68% within one standard deviation and some beyond two
+    let gaussian = deltas(&GaussianMutation::new(1.0, 2.0), &mut rng);
+    let within = gaussian.iter().filter(|delta| delta.abs() < 2.0).count();
+    assert!((6500..7100).contains(&within));
+    assert!(gaussian.iter().any(|delta| delta.abs() > 4.0));
+
+    //half of the cauchy samples are within the scale, the tail goes way further
+    let cauchy = deltas(&CauchyMutation::new(1.0, 1.0), &mut rng);
+    let within = cauchy.iter().filter(|delta| delta.abs() < 1.0).count();
+    assert!((4700..5300).contains(&within));
+    assert!(cauchy.iter().any(|delta| delta.abs() > 100.0));
+
+    //step sizes appear, change and are inherited by children
+    let mutation = SelfAdaptiveMutation::new(1.0, 0.5);
+    let mut parent = Chromosome::new(vec![0.0; 16]);
+    mutation.mutate(&mut rng, &mut parent);
+    assert_eq!(parent.step_sizes().len(), 16);
+    assert!(parent.step_sizes().iter().all(|&step| step > 0.0 && step != 0.5));
+
+    let mut ga = GeneticAlgorithm::new(RouletteWheelSelection::new(), UniformCrossover::new(), GaussianMutation::new(0.0, 0.0));
+    let population = vec![TestIndividual::create_fit(parent.clone(), 1.0); 2];
+    let (children, _) = ga.evolve(&mut rng, population, &EvolutionPolicy::generational());
+    assert!(children.iter().all(|child| child.chromosome().step_sizes() == parent.step_sizes()));
+}
+
+#[test]
+fn test_mutation_schedule() {
+    assert_eq!(Schedule::LinearDecay { to: 0.5, over: 10 }.factor(5), 0.75);
+    assert_eq!(Schedule::LinearDecay { to: 0.5, over: 10 }.factor(50), 0.5);
+    assert_eq!(Schedule::ExponentialDecay { rate: 0.5, min: 0.2 }.factor(1), 0.5);
+    assert_eq!(Schedule::ExponentialDecay { rate: 0.5, min: 0.2 }.factor(3), 0.2);
+    assert_eq!(Schedule::Step { every: 10, factor: 0.5 }.factor(25), 0.25);
+    assert_eq!(Schedule::Cosine { to: 0.0, period: 10 }.factor(0), 1.0);
+    assert!((Schedule::Cosine { to: 0.0, period: 10 }.factor(5) - 0.5).abs() < 1e-6);
+    assert_eq!(Schedule::Cosine { to: 0.0, period: 10 }.factor(10), 1.0);
+
+    //stale generations boost both rates until the best fitness moves again
+    let schedule = MutationSchedule { stagnation: Some(StagnationBoost { patience: 2, factor: 4.0 }), ..Default::default() };
+    let mut scheduler = MutationScheduler::new(schedule, 0.1, 0.5);
+    assert_eq!(scheduler.next(1.0), (0.1, 0.5));
+    assert_eq!(scheduler.next(1.0), (0.1, 0.5));
+    assert_eq!(scheduler.next(0.5), (0.4, 2.0));
+    assert_eq!(scheduler.next(2.0), (0.1, 0.5));
+
+    let mut ga = GeneticAlgorithm::new(RouletteWheelSelection::new(), UniformCrossover::new(), GaussianMutation::new(0.1, 0.5))
+        .with_scheduler(MutationScheduler::new(MutationSchedule { size: Schedule::Step { every: 1, factor: 0.5 }, ..Default::default() }, 0.1, 0.5));
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let population = vec![TestIndividual::create_fit(Chromosome::new(vec![0.0]), 1.0); 2];
+    let (population, stats) = ga.evolve(&mut rng, population, &EvolutionPolicy::generational());
+    assert_eq!((stats.mutation_chance(), stats.mutation_size()), (Some(0.1), Some(0.5)));
+    let (_, stats) = ga.evolve(&mut rng, population, &EvolutionPolicy::generational());
+    assert_eq!(stats.mutation_size(), Some(0.25));
+}
+
+#[test]
+fn test_cmaes() {
+    //maximizes the negative squared distance to a point, with stretched axes so the covariance has something to learn
+    let target = [1.0, -2.0, 0.5, 3.0];
+    let fitness = |chromosome: &Chromosome| -chromosome.iter().zip(target).enumerate()
+        .map(|(idx, (gene, goal))| (idx + 1) as f32 * (gene - goal).powi(2)).sum::<f32>();
+
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let mut cmaes = CmaEs::new(1.0);
+    let mut population: Vec<TestIndividual> = (0..16).map(|_| TestIndividual::create_fit(Chromosome::new(vec![0.0; 4]), 0.0)).collect();
+    for _ in 0..150 {
+        let scored = population.into_iter().map(|ind| TestIndividual::create_fit(ind.chromosome.clone(), fitness(&ind.chromosome))).collect();
+        (population, _) = Optimizer::evolve(&mut cmaes, &mut rng, scored, &EvolutionPolicy::default());
+    }
+
+    assert_eq!(population.len(), 16);
+    assert!(population.iter().all(|ind| fitness(ind.chromosome()) > -1e-3));
+    assert!(cmaes.sigma() < 0.1);
+
+    //the whole state survives a checkpoint
+    let restored: CmaEs = serde_json::from_str(&serde_json::to_string(&cmaes).unwrap()).unwrap();
+    assert_eq!(restored.dimension(), Some(4));
+    assert_eq!(restored.sigma(), cmaes.sigma());
+}
+
+#[test]
+fn test_openai_es() {
+    let target = [1.0, -2.0, 0.5, 3.0];
+    let fitness = |chromosome: &Chromosome| -chromosome.iter().zip(target).map(|(gene, goal)| (gene - goal).powi(2)).sum::<f32>();
+
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let mut es = OpenAiEs::new(0.1, 0.05);
+    let mut population: Vec<TestIndividual> = (0..31).map(|_| TestIndividual::create_fit(Chromosome::new(vec![0.0; 4]), 0.0)).collect();
+    for _ in 0..300 {
+        let scored = population.into_iter().map(|ind| TestIndividual::create_fit(ind.chromosome.clone(), fitness(&ind.chromosome))).collect();
+        (population, _) = Optimizer::evolve(&mut es, &mut rng, scored, &EvolutionPolicy::default());
+    }
+
+    //antithetic pairs around the mean, which ends up close to the target
+    assert_eq!(population.len(), 31);
+    let mean = es.mean().unwrap();
+    for (a, b) in population[0].chromosome().iter().zip(population[1].chromosome().iter()).zip(mean).map(|((a, b), m)| (a - m, m - b)) {
+        assert!((a - b).abs() < 1e-5);
+    }
+    assert_eq!(population[30].chromosome().iter().copied().collect::<Vec<_>>(), mean);
+    assert!(mean.iter().zip(target).all(|(m, goal)| (m - goal).abs() < 0.1));
+}
+
+#[test]
+fn test_novelty_search() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let search = NoveltySearch { k: 2, weight: 0.5, add_chance: 1.0, capacity: 3 };
+    let mut archive = NoveltyArchive::new(search);
+
+    //the outlier is the most novel, the two that are alike the least
+    let descriptors = vec![vec![0.0, 0.0], vec![0.2, 0.0], vec![0.1, 1.0]];
+    let novelty = archive.score(&mut rng, &descriptors);
+    assert!(novelty[2] > novelty[0] && novelty[2] > novelty[1]);
+    assert!((novelty[0] - novelty[1]).abs() < 1e-6);
+    assert_eq!(archive.len(), 3);
+
+    //the archive remembers them, coming back isn't novel anymore
+    let again = archive.score(&mut rng, &[vec![0.1, 1.0], vec![5.0, 5.0]]);
+    assert!(again[0] < again[1]);
+    assert_eq!(archive.len(), 3);
+
+    //equal weights mix the normalized fitness and novelty
+    assert_eq!(search.blend(&[10.0, 20.0, 30.0], &[1.0, 0.5, 0.0]), vec![0.5, 0.5, 0.5]);
+    assert_eq!(NoveltySearch { weight: 1.0, ..search }.blend(&[1.0, 2.0], &[3.0, 1.0]), vec![1.0, 0.0]);
+}
+
+#[test]
+fn test_map_elites() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let dimensions = vec![Dimension { min: 0.0, max: 1.0, bins: 2 }, Dimension { min: 0.0, max: 3.0, bins: 3 }];
+    let mut map = MapElites::new(dimensions, UniformCrossover::new(), GaussianMutation::new(0.5, 0.1));
+    assert_eq!(map.cells(), 6);
+
+    //out of range behaviours end up in the border cells, only the fittest of a cell stays
+    let population = vec![
+        TestIndividual::create_fit(Chromosome::new(vec![1.0]), 1.0),
+        TestIndividual::create_fit(Chromosome::new(vec![2.0]), 2.0),
+        TestIndividual::create_fit(Chromosome::new(vec![3.0]), 0.5),
+        TestIndividual::create_fit(Chromosome::new(vec![4.0]), 4.0),
+    ];
+    let behaviours = vec![vec![0.1, 0.5], vec![-1.0, 0.9], vec![0.9, 2.5], vec![0.6, 10.0]];
+    let (children, _) = map.evolve_described(&mut rng, population, &behaviours);
+
+    assert_eq!(children.len(), 4);
+    assert_eq!(map.filled(), 2);
+    assert_eq!(map.fitness_at(&[0, 0]), Some(2.0));
+    assert_eq!(map.fitness_at(&[1, 2]), Some(4.0));
+    assert_eq!(map.fitness_at(&[1, 1]), None);
+    //the children descend from the elites
+    assert!(children.iter().all(|child| (child.chromosome()[0] - 2.0).abs() < 1.0 || (child.chromosome()[0] - 4.0).abs() < 1.0));
+
+    let table = map.table(&["speed", "turn"]);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 7);
+    assert_eq!(lines[0], "speed,turn,fitness");
+    assert_eq!(lines[1], "0.25,0.5,2");
+    assert_eq!(lines[2], "0.25,1.5,");
+    assert_eq!(lines[6], "0.75,2.5,4");
+}
+
+#[test]
+fn test_nsga2() {
+    //maximizes both, (3, 1) (2, 2) (1, 3) trade off, (1, 1) is dominated by (2, 2) and (0, 0) by everyone
+    let objectives = vec![vec![3.0, 1.0], vec![1.0, 1.0], vec![2.0, 2.0], vec![0.0, 0.0], vec![1.0, 3.0], vec![2.5, 1.5]];
+    let mut fronts = non_dominated_sort(&objectives);
+    fronts.iter_mut().for_each(|front| front.sort());
+    assert_eq!(fronts, vec![vec![0, 2, 4, 5], vec![1], vec![3]]);
+
+    //the extremes are infinitely far, (2.5, 1.5) sits closer to its neighbours than (2, 2)
+    let distances = crowding_distance(&objectives, &fronts[0]);
+    assert!(distances[0].is_infinite() && distances[2].is_infinite());
+    assert!(distances[1] > distances[3]);
+
+    //two objectives read off the chromosome, the survivors outlive a generation that is dominated all over
+    struct TwoGoals(Chromosome);
+    impl Individual for TwoGoals {
+        fn fitness(&self) -> f32 {
+            self.0.iter().sum()
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+
+        fn create_fit(chromosome: Chromosome, _fitness: f32) -> Self {
+            Self(chromosome)
+        }
+
+        fn objectives(&self) -> Vec<f32> {
+            self.0.iter().copied().collect()
+        }
     }
-}
\ No newline at end of file
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let mut nsga2 = Nsga2::new(UniformCrossover::new(), GaussianMutation::new(0.0, 0.0));
+    let genes = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [0.5, 0.5]];
+    let population: Vec<TwoGoals> = genes.iter().map(|genes| TwoGoals(Chromosome::new(genes.to_vec()))).collect();
+    let (children, stats) = Optimizer::evolve(&mut nsga2, &mut rng, population, &EvolutionPolicy::default());
+    assert_eq!(children.len(), 4);
+    assert_eq!(stats.front().unwrap(), &[vec![0.0, 1.0], vec![0.5, 0.5], vec![1.0, 0.0]]);
+
+    let population: Vec<TwoGoals> = (0..4).map(|_| TwoGoals(Chromosome::new(vec![0.0, 0.0]))).collect();
+    let (_, stats) = Optimizer::evolve(&mut nsga2, &mut rng, population, &EvolutionPolicy::default());
+    assert_eq!(stats.front().unwrap(), &[vec![0.0, 1.0], vec![0.5, 0.5], vec![1.0, 0.0]]);
+    assert_eq!(stats.max_fitness(), 0.0);
+
+    //NaN objectives are ordered too instead of panicking
+    let mut nsga2 = Nsga2::new(UniformCrossover::new(), GaussianMutation::new(0.0, 0.0));
+    let genes = [[f32::NAN, 0.0], [1.0, 0.0], [0.0, f32::NAN], [0.5, 0.5]];
+    let population: Vec<TwoGoals> = genes.iter().map(|genes| TwoGoals(Chromosome::new(genes.to_vec()))).collect();
+    let (children, stats) = Optimizer::evolve(&mut nsga2, &mut rng, population, &EvolutionPolicy::default());
+    assert_eq!(children.len(), 4);
+    assert!(!stats.front().unwrap().is_empty());
+}
@@ -0,0 +1,171 @@
+use crate::*;
+
+/// One axis of the MAP-Elites grid, behaviours outside of `min..max` fall into the first or last bin
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Dimension {
+    pub min: f32,
+    pub max: f32,
+    pub bins: usize,
+}
+
+impl Dimension {
+    pub fn bin(&self, value: f32) -> usize {
+        let progress = (value - self.min) / (self.max - self.min);
+        ((progress * self.bins as f32) as isize).clamp(0, self.bins as isize - 1) as usize
+    }
+
+    pub fn center(&self, bin: usize) -> f32 {
+        self.min + (self.max - self.min) * (bin as f32 + 0.5) / self.bins as f32
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Elite {
+    chromosome: Chromosome,
+    fitness: f32,
+}
+
+/// Quality diversity after Mouret and Clune "Illuminating search spaces by mapping elites".
+/// Every cell of a grid over the behaviour dimensions keeps the fittest individual that behaved like that,
+/// offspring are bred from random elites of the whole grid instead of the last population
+pub struct MapElites {
+    dimensions: Vec<Dimension>,
+    cells: Vec<Option<Elite>>,
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Box<dyn MutationMethod>,
+    scheduler: Option<MutationScheduler>,
+}
+
+impl MapElites {
+    pub fn new(dimensions: Vec<Dimension>, crossover_method: impl CrossoverMethod + 'static, mutation_method: impl MutationMethod + 'static) -> Self {
+        assert!(!dimensions.is_empty());
+        assert!(dimensions.iter().all(|dimension| dimension.bins > 0 && dimension.max > dimension.min));
+
+        let cells = dimensions.iter().map(|dimension| dimension.bins).product();
+        Self {
+            dimensions,
+            cells: vec![None; cells],
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+            scheduler: None,
+        }
+    }
+
+    /// Adjusts the mutation rates before every generation like `GeneticAlgorithm::with_scheduler`,
+    /// stagnation is measured on the best elite of the grid
+    pub fn with_scheduler(mut self, scheduler: MutationScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    pub fn dimensions(&self) -> &[Dimension] {
+        &self.dimensions
+    }
+
+    pub fn cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn filled(&self) -> usize {
+        self.cells.iter().flatten().count()
+    }
+
+    /// Fitness of the elite of the cell at the given bins, None if nothing behaved like that yet
+    pub fn fitness_at(&self, bins: &[usize]) -> Option<f32> {
+        assert_eq!(bins.len(), self.dimensions.len());
+        let index = bins.iter().zip(&self.dimensions).fold(0, |index, (&bin, dimension)| index * dimension.bins + bin);
+        self.cells[index].as_ref().map(|elite| elite.fitness)
+    }
+
+    //row-major, the first dimension changes slowest. Missing coordinates count as the first bin
+    fn cell(&self, behaviour: &[f32]) -> usize {
+        self.dimensions.iter().enumerate().fold(0, |index, (idx, dimension)| {
+            index * dimension.bins + behaviour.get(idx).map_or(0, |&value| dimension.bin(value))
+        })
+    }
+
+    /// Keeps the chromosome if its cell is empty or held by a less fit elite
+    pub fn insert(&mut self, chromosome: &Chromosome, fitness: f32, behaviour: &[f32]) -> bool {
+        let cell = self.cell(behaviour);
+        if self.cells[cell].as_ref().is_some_and(|elite| elite.fitness >= fitness) {
+            return false;
+        }
+        self.cells[cell] = Some(Elite { chromosome: chromosome.clone(), fitness });
+        true
+    }
+
+    /// The grid as a table with one line per cell, the centers of its bins and the fitness of its elite,
+    /// left blank for empty cells. `names` head the dimension columns
+    pub fn table(&self, names: &[&str]) -> String {
+        assert_eq!(names.len(), self.dimensions.len());
+
+        let mut text = names.join(",");
+        text.push_str(",fitness\n");
+        for (index, cell) in self.cells.iter().enumerate() {
+            let mut rest = index;
+            let mut centers = vec![0.0; self.dimensions.len()];
+            for (center, dimension) in centers.iter_mut().zip(&self.dimensions).rev() {
+                *center = dimension.center(rest % dimension.bins);
+                rest /= dimension.bins;
+            }
+            for center in centers {
+                text.push_str(&format!("{center},"));
+            }
+            if let Some(elite) = cell {
+                text.push_str(&elite.fitness.to_string());
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// `behaviours` lines up with the population, every individual competes for the cell its behaviour falls in
+    pub fn evolve_described<I>(&mut self, rng: &mut dyn RngCore, population: Vec<I>, behaviours: &[Vec<f32>]) -> (Vec<I>, Statistics) where I: Individual {
+        assert!(!population.is_empty());
+        assert_eq!(population.len(), behaviours.len());
+
+        let mut stats = Statistics::new(&population);
+        for (individual, behaviour) in population.iter().zip(behaviours) {
+            self.insert(individual.chromosome(), individual.fitness(), behaviour);
+        }
+
+        let elites: Vec<&Elite> = self.cells.iter().flatten().collect();
+        if let Some(scheduler) = &mut self.scheduler {
+            let best = elites.iter().map(|elite| elite.fitness).fold(f32::NEG_INFINITY, f32::max);
+            let (chance, size) = scheduler.next(best);
+            self.mutation_method.set_rates(chance, size);
+            stats = stats.with_mutation_rates(chance, size);
+        }
+
+        let children = (0..population.len()).map(|_| {
+            let parent1 = &elites.choose(rng).unwrap().chromosome;
+            let parent2 = &elites.choose(rng).unwrap().chromosome;
+            let mut child = self.crossover_method.crossover(rng, parent1, parent2);
+            child.set_step_sizes(inherit_step_sizes(parent1, parent2));
+            self.mutation_method.mutate(rng, &mut child);
+
+            I::create(child)
+        }).collect();
+
+        (children, stats)
+    }
+}
+
+impl<I> Optimizer<I> for MapElites where I: Individual {
+    /// Without behaviours everybody competes for the first cell, see `Optimizer::evolve_described`.
+    /// The grid is the elitism, the policy doesn't apply
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: Vec<I>, _policy: &EvolutionPolicy) -> (Vec<I>, Statistics) {
+        let behaviours = vec![Vec::new(); population.len()];
+        MapElites::evolve_described(self, rng, population, &behaviours)
+    }
+
+    fn evolve_described(&mut self, rng: &mut dyn RngCore, population: Vec<I>, behaviours: &[Vec<f32>], _policy: &EvolutionPolicy) -> (Vec<I>, Statistics) {
+        MapElites::evolve_described(self, rng, population, behaviours)
+    }
+
+    fn set_generation(&mut self, generation: usize) {
+        if let Some(scheduler) = &mut self.scheduler {
+            scheduler.set_generation(generation);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::*;
+
+/// Novelty search after Lehman and Stanley "Abandoning Objectives: Evolution Through the Search for Novelty Alone".
+/// An individual is as novel as its behaviour descriptor is far from the `k` nearest ones of the population and the archive
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NoveltySearch {
+    pub k: usize,
+    /// Share of the novelty in the blended score, 1.0 ignores the fitness altogether
+    pub weight: f32,
+    /// Chance of every descriptor to make it into the archive
+    pub add_chance: f32,
+    /// Random entries make room once the archive is full
+    pub capacity: usize,
+}
+
+impl Default for NoveltySearch {
+    fn default() -> Self {
+        Self { k: 15, weight: 0.5, add_chance: 0.05, capacity: 1000 }
+    }
+}
+
+impl NoveltySearch {
+    /// Both sides are min-max normalized first, so the weight doesn't depend on the scale of the fitness
+    pub fn blend(&self, fitness: &[f32], novelty: &[f32]) -> Vec<f32> {
+        assert_eq!(fitness.len(), novelty.len());
+
+        let normalize = |values: &[f32]| -> Vec<f32> {
+            let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let range = max - min;
+            values.iter().map(|value| if range > 0.0 { (value - min) / range } else { 0.0 }).collect()
+        };
+
+        normalize(fitness).into_iter().zip(normalize(novelty))
+            .map(|(f, n)| (1.0 - self.weight) * f + self.weight * n)
+            .collect()
+    }
+}
+
+/// Behaviours seen in earlier generations, so going back to them isn't novel again
+#[derive(Clone, Debug)]
+pub struct NoveltyArchive {
+    search: NoveltySearch,
+    archive: Vec<Vec<f32>>,
+}
+
+impl NoveltyArchive {
+    pub fn new(search: NoveltySearch) -> Self {
+        assert!(search.k > 0);
+        assert!((0.0..=1.0).contains(&search.weight));
+        assert!((0.0..=1.0).contains(&search.add_chance));
+
+        Self { search, archive: Vec::new() }
+    }
+
+    pub fn search(&self) -> &NoveltySearch {
+        &self.search
+    }
+
+    pub fn len(&self) -> usize {
+        self.archive.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.archive.is_empty()
+    }
+
+    /// Novelty of every descriptor, the population is scored before any of it is archived
+    pub fn score(&mut self, rng: &mut dyn RngCore, descriptors: &[Vec<f32>]) -> Vec<f32> {
+        let scores = descriptors.iter().enumerate().map(|(idx, descriptor)| {
+            let mut distances: Vec<f32> = descriptors.iter().enumerate()
+                .filter(|&(other, _)| other != idx)
+                .map(|(_, other)| other)
+                .chain(&self.archive)
+                .map(|other| distance(descriptor, other))
+                .collect();
+            if distances.is_empty() {
+                return 0.0;
+            }
+
+            let k = self.search.k.min(distances.len());
+            distances.select_nth_unstable_by(k - 1, |a, b| a.total_cmp(b));
+            distances[..k].iter().sum::<f32>() / k as f32
+        }).collect();
+
+        for descriptor in descriptors {
+            if !rng.gen_bool(self.search.add_chance as f64) {
+                continue;
+            }
+            if self.archive.len() < self.search.capacity {
+                self.archive.push(descriptor.clone());
+            } else if self.search.capacity > 0 {
+                let replaced = rng.gen_range(0..self.archive.len());
+                self.archive[replaced] = descriptor.clone();
+            }
+        }
+
+        scores
+    }
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len());
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f32>().sqrt()
+}
@@ -0,0 +1,189 @@
+use crate::*;
+
+//NSGA-II after Deb et al. "A Fast and Elitist Multiobjective Genetic Algorithm: NSGA-II", every objective is maximized
+
+fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+/// Indices of the population by Pareto front, the first front is dominated by nobody,
+/// the second only by the first and so on
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut domination_count = vec![0; n];
+    for a in 0..n {
+        for b in a + 1..n {
+            if dominates(&objectives[a], &objectives[b]) {
+                dominated[a].push(b);
+                domination_count[b] += 1;
+            } else if dominates(&objectives[b], &objectives[a]) {
+                dominated[b].push(a);
+                domination_count[a] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..n).filter(|&idx| domination_count[idx] == 0).collect();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &idx in &front {
+            for &other in &dominated[idx] {
+                domination_count[other] -= 1;
+                if domination_count[other] == 0 {
+                    next.push(other);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/// How far apart the neighbours of every member of `front` are, summed over the objectives
+/// each normalized to its range. The extremes of every objective are infinitely far
+pub fn crowding_distance(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distances = vec![0.0; front.len()];
+    let Some(first) = front.first() else {
+        return distances;
+    };
+
+    for objective in 0..objectives[*first].len() {
+        add_crowding(objectives, front, objective, &mut distances);
+    }
+    distances
+}
+
+fn add_crowding(objectives: &[Vec<f32>], front: &[usize], objective: usize, distances: &mut [f32]) {
+    let mut order: Vec<usize> = (0..front.len()).collect();
+    order.sort_by(|&a, &b| objectives[front[a]][objective].total_cmp(&objectives[front[b]][objective]));
+    let value = |position: usize| objectives[front[order[position]]][objective];
+    let range = value(front.len() - 1) - value(0);
+
+    distances[order[0]] = f32::INFINITY;
+    distances[order[front.len() - 1]] = f32::INFINITY;
+    if range <= 0.0 {
+        return;
+    }
+    for position in 1..front.len() - 1 {
+        distances[order[position]] += (value(position + 1) - value(position - 1)) / range;
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Survivor {
+    chromosome: Chromosome,
+    objectives: Vec<f32>,
+    rank: usize,
+    crowding: f32,
+}
+
+impl Survivor {
+    //the crowded comparison, a better front wins and within a front the less crowded one does
+    fn beats(&self, other: &Survivor) -> bool {
+        self.rank < other.rank || (self.rank == other.rank && self.crowding > other.crowding)
+    }
+}
+
+/// Multi-objective optimizer over `Individual::objectives`. Every generation the survivors of the last one
+/// are merged with the newly evaluated population, the best fronts make the next survivors and the front that
+/// doesn't fit whole is cut by crowding distance. Parents are picked from the survivors by binary tournaments
+/// on the crowded comparison. The fitness is only reported, elites and parent pools don't apply
+pub struct Nsga2 {
+    survivors: Vec<Survivor>,
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Box<dyn MutationMethod>,
+    scheduler: Option<MutationScheduler>,
+}
+
+impl Nsga2 {
+    pub fn new(crossover_method: impl CrossoverMethod + 'static, mutation_method: impl MutationMethod + 'static) -> Self {
+        Self { survivors: Vec::new(), crossover_method: Box::new(crossover_method), mutation_method: Box::new(mutation_method), scheduler: None }
+    }
+
+    /// Adjusts the mutation rates before every generation like `GeneticAlgorithm::with_scheduler`,
+    /// stagnation is measured on the best fitness of the evaluated population
+    pub fn with_scheduler(mut self, scheduler: MutationScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Objectives of the current first front, every distinct trade-off once
+    pub fn front(&self) -> Vec<Vec<f32>> {
+        let mut front: Vec<Vec<f32>> = self.survivors.iter().filter(|survivor| survivor.rank == 0).map(|survivor| survivor.objectives.clone()).collect();
+        //element-wise total order, so NaN objectives can't make logging the front panic
+        front.sort_by(|a, b| a.iter().zip(b).map(|(x, y)| x.total_cmp(y)).find(|order| order.is_ne()).unwrap_or(a.len().cmp(&b.len())));
+        front.dedup();
+        front
+    }
+
+    //keeps the best `size` of the last survivors and the new population
+    fn survive(&mut self, evaluated: Vec<(Chromosome, Vec<f32>)>, size: usize) {
+        let mut merged: Vec<(Chromosome, Vec<f32>)> = self.survivors.drain(..).map(|survivor| (survivor.chromosome, survivor.objectives)).collect();
+        //a different number of objectives can't be compared, the old survivors make way
+        if merged.first().zip(evaluated.first()).is_some_and(|(old, new)| old.1.len() != new.1.len()) {
+            merged.clear();
+        }
+        merged.extend(evaluated);
+
+        let objectives: Vec<Vec<f32>> = merged.iter().map(|(_, objectives)| objectives.clone()).collect();
+        let mut merged: Vec<Option<(Chromosome, Vec<f32>)>> = merged.into_iter().map(Some).collect();
+        for (rank, front) in non_dominated_sort(&objectives).into_iter().enumerate() {
+            let left = size - self.survivors.len();
+            if left == 0 {
+                break;
+            }
+
+            let mut ranked: Vec<(usize, f32)> = front.iter().copied().zip(crowding_distance(&objectives, &front)).collect();
+            if ranked.len() > left {
+                ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+                ranked.truncate(left);
+            }
+            self.survivors.extend(ranked.into_iter().map(|(idx, crowding)| {
+                let (chromosome, objectives) = merged[idx].take().unwrap();
+                Survivor { chromosome, objectives, rank, crowding }
+            }));
+        }
+    }
+
+    fn tournament(&self, rng: &mut dyn RngCore) -> &Survivor {
+        let a = self.survivors.choose(rng).unwrap();
+        let b = self.survivors.choose(rng).unwrap();
+        if b.beats(a) { b } else { a }
+    }
+}
+
+impl<I> Optimizer<I> for Nsga2 where I: Individual {
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: Vec<I>, _policy: &EvolutionPolicy) -> (Vec<I>, Statistics) {
+        assert!(!population.is_empty());
+
+        let mut stats = Statistics::new(&population);
+        let size = population.len();
+        self.survive(population.iter().map(|individual| (individual.chromosome().clone(), individual.objectives())).collect(), size);
+        if let Some(scheduler) = &mut self.scheduler {
+            let (chance, size) = scheduler.next(stats.max_fitness());
+            self.mutation_method.set_rates(chance, size);
+            stats = stats.with_mutation_rates(chance, size);
+        }
+
+        let children = (0..size).map(|_| {
+            let parent1 = &self.tournament(rng).chromosome;
+            let parent2 = &self.tournament(rng).chromosome;
+            let mut child = self.crossover_method.crossover(rng, parent1, parent2);
+            child.set_step_sizes(inherit_step_sizes(parent1, parent2));
+            self.mutation_method.mutate(rng, &mut child);
+
+            I::create(child)
+        }).collect();
+
+        (children, stats.with_front(self.front()))
+    }
+
+    fn set_generation(&mut self, generation: usize) {
+        if let Some(scheduler) = &mut self.scheduler {
+            scheduler.set_generation(generation);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::*;
+
+/// Natural evolution strategy after Salimans et al. "Evolution Strategies as a Scalable Alternative to Reinforcement Learning".
+/// The population is the mean plus and minus the same gaussian noise in neighbouring pairs, their fitness ranks
+/// estimate a gradient and Adam follows it. An odd population ends with the unperturbed mean
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenAiEs {
+    sigma: f32,
+    learning_rate: f32,
+    //None until the first population comes in
+    mean: Option<Vec<f32>>,
+    //Adam moments
+    velocity: Vec<f32>,
+    momentum: Vec<f32>,
+    steps: i32,
+}
+
+const BETA1: f32 = 0.9;
+const BETA2: f32 = 0.999;
+const EPSILON: f32 = 1e-8;
+
+impl OpenAiEs {
+    /// `sigma` is the noise scale, `learning_rate` the Adam step size
+    pub fn new(sigma: f32, learning_rate: f32) -> Self {
+        assert!(sigma > 0.0);
+        assert!(learning_rate > 0.0);
+
+        Self { sigma, learning_rate, mean: None, velocity: Vec::new(), momentum: Vec::new(), steps: 0 }
+    }
+
+    pub fn mean(&self) -> Option<&[f32]> {
+        self.mean.as_deref()
+    }
+
+    fn restart(&mut self, mean: Vec<f32>) {
+        self.velocity = vec![0.0; mean.len()];
+        self.momentum = vec![0.0; mean.len()];
+        self.mean = Some(mean);
+        self.steps = 0;
+    }
+
+    //the noise is read back from the chromosomes, so nothing but the mean has to be kept between generations
+    fn step<I>(&mut self, population: &[I]) where I: Individual {
+        let mean = self.mean.as_mut().unwrap();
+        let pairs = population.len() / 2;
+        if pairs == 0 {
+            return;
+        }
+
+        //centered ranks in [-0.5, 0.5], the scale of the fitness doesn't matter
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|&a, &b| population[a].fitness().total_cmp(&population[b].fitness()));
+        let mut utilities = vec![0.0; population.len()];
+        for (rank, &idx) in order.iter().enumerate() {
+            utilities[idx] = rank as f32 / (population.len() - 1).max(1) as f32 - 0.5;
+        }
+
+        let mut gradient = vec![0.0; mean.len()];
+        for pair in 0..pairs {
+            let weight = utilities[2 * pair] - utilities[2 * pair + 1];
+            for ((g, &x), &m) in gradient.iter_mut().zip(population[2 * pair].chromosome().iter()).zip(mean.iter()) {
+                *g += weight * (x - m) / self.sigma;
+            }
+        }
+
+        self.steps += 1;
+        let correction = (1.0 - BETA2.powi(self.steps)).sqrt() / (1.0 - BETA1.powi(self.steps));
+        for (((m, g), momentum), velocity) in mean.iter_mut().zip(gradient).zip(&mut self.momentum).zip(&mut self.velocity) {
+            let g = g / (2 * pairs) as f32 / self.sigma;
+            *momentum = BETA1 * *momentum + (1.0 - BETA1) * g;
+            *velocity = BETA2 * *velocity + (1.0 - BETA2) * g * g;
+            //ascent, higher fitness is better
+            *m += self.learning_rate * correction * *momentum / (velocity.sqrt() + EPSILON);
+        }
+    }
+}
+
+impl<I> Optimizer<I> for OpenAiEs where I: Individual {
+    /// Elites and parent pools don't apply, every individual is a sample around the mean
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: Vec<I>, _policy: &EvolutionPolicy) -> (Vec<I>, Statistics) {
+        assert!(!population.is_empty());
+
+        let stats = Statistics::new(&population);
+        let n = population[0].chromosome().len();
+        if self.mean.as_ref().is_some_and(|mean| mean.len() == n) {
+            self.step(&population);
+        } else {
+            //the first population wasn't drawn around any mean, its best individual becomes one
+            let best = population.iter().max_by(|a, b| a.fitness().total_cmp(&b.fitness())).unwrap();
+            self.restart(best.chromosome().iter().copied().collect());
+        }
+
+        let mean = self.mean.as_ref().unwrap();
+        let mut children = Vec::with_capacity(population.len());
+        for _ in 0..population.len() / 2 {
+            let noise: Vec<f32> = (0..n).map(|_| self.sigma * rng.sample::<f32, _>(StandardNormal)).collect();
+            children.push(I::create(mean.iter().zip(&noise).map(|(m, e)| m + e).collect()));
+            children.push(I::create(mean.iter().zip(&noise).map(|(m, e)| m - e).collect()));
+        }
+        if population.len() % 2 == 1 {
+            children.push(I::create(Chromosome::new(mean.clone())));
+        }
+
+        (children, stats.with_mutation_rates(1.0, self.sigma))
+    }
+}
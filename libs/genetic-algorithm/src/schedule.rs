@@ -0,0 +1,97 @@
+use crate::*;
+
+/// Multiplier on a base mutation rate over the generations, 1.0 in generation zero
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum Schedule {
+    #[default]
+    Constant,
+    /// Falls linearly to `to` over `over` generations and stays there
+    LinearDecay { to: f32, over: usize },
+    /// Shrinks by `rate` every generation but never below `min`
+    ExponentialDecay { rate: f32, min: f32 },
+    /// Multiplied by `factor` every `every` generations
+    Step { every: usize, factor: f32 },
+    /// Follows half a cosine down to `to` and jumps back up every `period` generations
+    Cosine { to: f32, period: usize },
+}
+
+impl Schedule {
+    pub fn factor(&self, generation: usize) -> f32 {
+        match *self {
+            Schedule::Constant => 1.0,
+            Schedule::LinearDecay { to, over } => {
+                let progress = if over == 0 { 1.0 } else { (generation as f32 / over as f32).min(1.0) };
+                1.0 + (to - 1.0) * progress
+            },
+            Schedule::ExponentialDecay { rate, min } => rate.powf(generation as f32).max(min),
+            Schedule::Step { every, factor } => factor.powi((generation / every.max(1)) as i32),
+            Schedule::Cosine { to, period } => {
+                let progress = (generation % period.max(1)) as f32 / period.max(1) as f32;
+                to + (1.0 - to) * (1.0 + (std::f32::consts::PI * progress).cos()) / 2.0
+            },
+        }
+    }
+}
+
+/// Multiplies the rates by `factor` once the best fitness hasn't improved for `patience` generations,
+/// until it does again
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StagnationBoost {
+    pub patience: usize,
+    pub factor: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct MutationSchedule {
+    /// Applied to the mutation chance, the result is capped at 1.0
+    pub chance: Schedule,
+    /// Applied to the mutation size
+    pub size: Schedule,
+    pub stagnation: Option<StagnationBoost>,
+}
+
+/// Keeps track of the generation and the stagnation of one population
+#[derive(Clone, Debug)]
+pub struct MutationScheduler {
+    schedule: MutationSchedule,
+    chance: f32,
+    size: f32,
+    generation: usize,
+    best: f32,
+    stale: usize,
+}
+
+impl MutationScheduler {
+    /// `chance` and `size` are the base rates the schedule is applied to
+    pub fn new(schedule: MutationSchedule, chance: f32, size: f32) -> Self {
+        Self { schedule, chance, size, generation: 0, best: f32::NEG_INFINITY, stale: 0 }
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn set_generation(&mut self, generation: usize) {
+        self.generation = generation;
+    }
+
+    /// Rates for the children of a generation whose best individual scored `best_fitness`
+    pub fn next(&mut self, best_fitness: f32) -> (f32, f32) {
+        if best_fitness > self.best {
+            self.best = best_fitness;
+            self.stale = 0;
+        } else {
+            self.stale += 1;
+        }
+        let boost = match self.schedule.stagnation {
+            Some(StagnationBoost { patience, factor }) if self.stale >= patience => factor,
+            _ => 1.0,
+        };
+
+        let chance = (self.chance * self.schedule.chance.factor(self.generation) * boost).clamp(0.0, 1.0);
+        let size = self.size * self.schedule.size.factor(self.generation) * boost;
+        self.generation += 1;
+
+        (chance, size)
+    }
+}
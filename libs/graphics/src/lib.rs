@@ -117,8 +117,10 @@ fn draw_ui(cur_stats: &mut Option<(Statistics, Statistics)>,
     }
 
     if ui::root_ui().button(Vec2::new(10.0, 130.0), "Load Simulation") {
-        simulation.load_simulation(rng);
-        println!("Loaded simulation.");
+        match simulation.load_simulation(rng) {
+            Ok(()) => println!("Loaded simulation."),
+            Err(err) => println!("Could not load simulation: {err}"),
+        }
     }
 
     let mut info_label:String = String::from("Generation ");
@@ -130,7 +132,13 @@ fn draw_ui(cur_stats: &mut Option<(Statistics, Statistics)>,
 
 async fn draw_frames(sim_conf: SimulationConfig, hervor_conf: IndividualConfig, chaser_conf: IndividualConfig){
     let mut rng = rand::thread_rng();
-    let mut simulation: Simulation = Simulation::random(&mut rng, sim_conf, hervor_conf, chaser_conf);
+    let mut simulation: Simulation = match Simulation::random(&mut rng, sim_conf, hervor_conf, chaser_conf) {
+        Ok(simulation) => simulation,
+        Err(err) => {
+            println!("Invalid config: {err}");
+            return;
+        },
+    };
     let mut cur_stats:Option<(Statistics, Statistics)> = None;
 
     loop {
@@ -5,17 +5,36 @@ pub trait Individual {
     fn chromosome(&self) -> &Chromosome;
     fn create(chromosome: Chromosome) -> Self;
     fn create_fit(chromosome: Chromosome, fitness: f32) -> Self;
+
+    /// What multi-objective optimization maximizes, individuals with a single goal only have their fitness
+    fn objectives(&self) -> Vec<f32> {
+        vec![self.fitness()]
+    }
 }
 
 
 #[derive(Clone, Debug)]
 pub struct Chromosome {
     genes: Vec<f32>,
+    //one mutation step size per gene for self-adaptive mutation, empty otherwise
+    step_sizes: Vec<f32>,
 }
 
 impl Chromosome {
     pub fn new(genes: Vec<f32>) -> Self {
-        Self{genes: genes}
+        Self{genes: genes, step_sizes: Vec::new()}
+    }
+
+    pub fn step_sizes(&self) -> &[f32] {
+        &self.step_sizes
+    }
+
+    pub fn set_step_sizes(&mut self, step_sizes: Vec<f32>) {
+        self.step_sizes = step_sizes;
+    }
+
+    pub fn take_step_sizes(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.step_sizes)
     }
 
     pub fn len(&self) -> usize {
@@ -43,6 +62,7 @@ impl FromIterator<f32> for Chromosome {
     fn from_iter<T: IntoIterator<Item = f32>>(iter: T) -> Self {
         Self {
             genes: iter.into_iter().collect(),
+            step_sizes: Vec::new(),
         }
     }
 }
@@ -0,0 +1,113 @@
+use crate::*;
+use rand_distr::{Distribution, Normal};
+
+/// Named weight initialization schemes, fan-in counts the recurrent weights of a neuron too
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Init {
+    /// Every weight and bias from uniform(-range, range)
+    Uniform(f32),
+    /// Glorot, uniform(-limit, limit) with limit = sqrt(6 / (fan_in + fan_out)), suits tanh and sigmoid
+    Xavier,
+    /// normal(0, sqrt(2 / fan_in)), suits ReLU
+    He,
+    /// normal(0, gain / sqrt(fan_in))
+    ScaledNormal(f32),
+    Zeros,
+}
+
+impl Default for Init {
+    fn default() -> Self {
+        Init::Uniform(1.0)
+    }
+}
+
+impl Init {
+    pub(crate) fn sample(&self, rng: &mut dyn rand::RngCore, fan_in: usize, fan_out: usize) -> f32 {
+        let fan_in = fan_in.max(1) as f32;
+        let normal = |rng: &mut dyn rand::RngCore, std: f32| Normal::new(0.0, std).expect("std is finite").sample(rng);
+
+        match *self {
+            Init::Uniform(range) => rng.gen_range(-range..=range),
+            Init::Xavier => {
+                let limit = (6.0 / (fan_in + fan_out as f32)).sqrt();
+                rng.gen_range(-limit..=limit)
+            }
+            Init::He => normal(rng, (2.0 / fan_in).sqrt()),
+            Init::ScaledNormal(gain) => normal(rng, gain / fan_in.sqrt()),
+            Init::Zeros => 0.0,
+        }
+    }
+}
+
+enum WeightSource {
+    Explicit(Vec<f32>),
+    Init(Init),
+}
+
+/// Created by `Network::new`, validates the topology and weights before anything is built
+pub struct NetworkBuilder {
+    topology: Vec<LayerTopology>,
+    source: WeightSource,
+    zero_bias: bool,
+}
+
+impl NetworkBuilder {
+    pub(crate) fn new(topology: &[LayerTopology]) -> Self {
+        Self { topology: topology.to_vec(), source: WeightSource::Init(Init::default()), zero_bias: false }
+    }
+
+    /// Weights in the layout of `Network::weights()`
+    pub fn with_weights(mut self, weights: impl IntoIterator<Item = f32>) -> Self {
+        self.source = WeightSource::Explicit(weights.into_iter().collect());
+        self
+    }
+
+    pub fn with_init(mut self, init: Init) -> Self {
+        self.source = WeightSource::Init(init);
+        self
+    }
+
+    /// Starts every bias at zero instead of drawing it, explicit weights are left alone
+    pub fn with_zero_bias(mut self, zero_bias: bool) -> Self {
+        self.zero_bias = zero_bias;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), NetworkError> {
+        if self.topology.len() < 2 {
+            return Err(NetworkError::EmptyTopology);
+        }
+        if let Some(idx) = self.topology.iter().position(|layer| layer.neurons == 0) {
+            return Err(NetworkError::ZeroWidthLayer(idx));
+        }
+        if let WeightSource::Explicit(weights) = &self.source {
+            let expected = Network::weight_count(&self.topology);
+            if weights.len() != expected {
+                return Err(NetworkError::WrongWeightCount { expected, got: weights.len() });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `rng` is only drawn from when the weights come from an initialization scheme
+    pub fn build(self, rng: &mut dyn rand::RngCore) -> Result<Network, NetworkError> {
+        self.validate()?;
+
+        let layers = match self.source {
+            WeightSource::Explicit(weights) => {
+                let mut weights = weights.into_iter();
+                self.topology.windows(2)
+                    .map(|layers| Layer::from_weights(layers[0].neurons, layers[1].neurons, layers[1].activation, layers[1].kind, &mut weights))
+                    .collect()
+            }
+            WeightSource::Init(init) => {
+                self.topology.windows(2)
+                    .map(|layers| Layer::initialized(rng, layers[0].neurons, layers[1].neurons, layers[1].activation, layers[1].kind, init, self.zero_bias))
+                    .collect()
+            }
+        };
+
+        Ok(Network { layers })
+    }
+}
@@ -0,0 +1,69 @@
+use crate::*;
+use crate::neat::{Genome, NeatNetwork};
+
+//HyperNEAT style indirect encoding, a small NEAT genome (the CPPN) is queried with the coordinates
+//of two neurons and answers with the weight between them
+
+/// CPPN inputs: x and y of the source neuron, x and y of the target neuron, and a constant 1.0
+pub const CPPN_INPUTS: usize = 5;
+/// CPPN outputs: connection weight, bias of the target neuron
+pub const CPPN_OUTPUTS: usize = 2;
+/// Activations new CPPN nodes are drawn from, the periodic and symmetric ones are what give CPPNs their patterns
+pub const CPPN_ACTIVATIONS: [Activation; 5] = [Activation::Sin, Activation::Gaussian, Activation::Tanh, Activation::Sigmoid, Activation::Identity];
+
+//outputs below this magnitude leave the connection at 0.0, the rest is rescaled to (0, WEIGHT_RANGE]
+const EXPRESSION_THRESHOLD: f32 = 0.2;
+const WEIGHT_RANGE: f32 = 3.0;
+
+/// Starting point for evolving a CPPN, every input wired to every output
+pub fn minimal(rng: &mut dyn rand::RngCore) -> Genome {
+    Genome::minimal(rng, CPPN_INPUTS, CPPN_OUTPUTS, Activation::Sin, Activation::Tanh)
+}
+
+//neurons of a layer are spread evenly over x in [-1, 1], layers over y in [-1, 1] from input to output
+fn coordinate(idx: usize, count: usize) -> f32 {
+    if count < 2 { 0.0 } else { idx as f32 / (count - 1) as f32 * 2.0 - 1.0 }
+}
+
+fn express(output: f32) -> f32 {
+    if output.abs() < EXPRESSION_THRESHOLD {
+        return 0.0;
+    }
+    output.signum() * (output.abs() - EXPRESSION_THRESHOLD) / (1.0 - EXPRESSION_THRESHOLD) * WEIGHT_RANGE
+}
+
+impl Network {
+    /// Builds a network of `topology` whose weights are painted by `cppn`. Recurrent weights are
+    /// queried within the layer itself, the coefficients of plastic layers are left at 0.0.
+    pub fn from_cppn(cppn: &NeatNetwork, topology: &[LayerTopology]) -> Result<Self, NetworkError> {
+        assert_eq!((cppn.input_size(), cppn.output_size()), (CPPN_INPUTS, CPPN_OUTPUTS), "not a CPPN");
+        let mut network = Network::new(topology).with_init(Init::Zeros).build(&mut rand::rngs::mock::StepRng::new(0, 0))?;
+
+        let mut values = cppn.values();
+        let mut query = |from: (f32, f32), to: (f32, f32)| {
+            let mut output = [0.0; CPPN_OUTPUTS];
+            cppn.propagate_into(&[from.0, from.1, to.0, to.1, 1.0], &mut values, &mut output);
+            output
+        };
+
+        let depth = network.layers.len() + 1;
+        for (idx, layer) in network.layers.iter_mut().enumerate() {
+            let (y_in, y_out) = (coordinate(idx, depth), coordinate(idx + 1, depth));
+            let (input_size, output_size, stride) = (layer.input_size, layer.output_size, layer.stride());
+            let recurrent_size = layer.recurrent_size();
+
+            for (j, row) in layer.matrix.chunks_exact_mut(stride).enumerate() {
+                let to = (coordinate(j, output_size), y_out);
+                row[0] = express(query((0.0, y_in), to)[1]);
+                for k in 0..input_size {
+                    row[1 + k] = express(query((coordinate(k, input_size), y_in), to)[0]);
+                }
+                for k in 0..recurrent_size {
+                    row[1 + input_size + k] = express(query((coordinate(k, output_size), y_out), to)[0]);
+                }
+            }
+        }
+
+        Ok(network)
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkError {
+    /// Less than an input and an output layer
+    EmptyTopology,
+    /// Layer at this index of the topology has no neurons
+    ZeroWidthLayer(usize),
+    WrongWeightCount { expected: usize, got: usize },
+    UnsupportedVersion(u32),
+    /// Layer at this index of the topology can't be trained by backpropagation
+    NotTrainable(usize),
+    /// Sample at this index of a dataset doesn't fit the network's input or output size
+    SampleShape(usize),
+    /// Encoder and head of a `SetTopology` don't fit together
+    SetShape(&'static str),
+    /// Saved data that can't be decoded, e.g. truncated bytes or unknown codes
+    Corrupt(String),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::EmptyTopology => write!(f, "network needs at least an input and an output layer"),
+            NetworkError::ZeroWidthLayer(idx) => write!(f, "layer {idx} has no neurons"),
+            NetworkError::WrongWeightCount { expected, got } => write!(f, "got {got} weights, the topology needs {expected}"),
+            NetworkError::UnsupportedVersion(version) => write!(f, "unsupported network format version {version}, expected {}", crate::FORMAT_VERSION),
+            NetworkError::NotTrainable(idx) => write!(f, "layer {idx} can't be trained by backpropagation, only dense layers can"),
+            NetworkError::SampleShape(idx) => write!(f, "sample {idx} doesn't fit the network's input or output size"),
+            NetworkError::SetShape(reason) => write!(f, "invalid set topology: {reason}"),
+            NetworkError::Corrupt(reason) => write!(f, "corrupt network data: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
@@ -0,0 +1,106 @@
+use crate::*;
+use serde::{Serialize, Deserialize};
+
+/// Bumped whenever the saved layout of a network changes
+pub const FORMAT_VERSION: u32 = 1;
+const MAGIC: &[u8; 4] = b"RCNN";
+
+//what serde sees of a Network, everything needed to rebuild it without knowing the config
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedNetwork {
+    version: u32,
+    topology: Vec<LayerTopology>,
+    weights: Vec<f32>,
+}
+
+impl From<Network> for SavedNetwork {
+    fn from(network: Network) -> Self {
+        Self { version: FORMAT_VERSION, topology: network.topology(), weights: network.weights() }
+    }
+}
+
+impl TryFrom<SavedNetwork> for Network {
+    type Error = NetworkError;
+
+    fn try_from(saved: SavedNetwork) -> Result<Self, Self::Error> {
+        if saved.version != FORMAT_VERSION {
+            return Err(NetworkError::UnsupportedVersion(saved.version));
+        }
+
+        Network::try_from_weights(&saved.topology, saved.weights)
+    }
+}
+
+//little endian readers for Network::from_bytes
+fn read<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], NetworkError> {
+    if bytes.len() < len {
+        return Err(NetworkError::Corrupt(String::from("unexpected end of data")));
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, NetworkError> {
+    Ok(u32::from_le_bytes(read(bytes, 4)?.try_into().unwrap()))
+}
+
+fn read_f32(bytes: &mut &[u8]) -> Result<f32, NetworkError> {
+    Ok(f32::from_le_bytes(read(bytes, 4)?.try_into().unwrap()))
+}
+
+impl Network {
+    /// Compact binary form: magic, format version, topology, then every weight as little endian f32
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let topology = self.topology();
+        let weights = self.weights();
+
+        let mut bytes = Vec::with_capacity(16 + topology.len() * 10 + weights.len() * 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(topology.len() as u32).to_le_bytes());
+        for layer in &topology {
+            let (code, param) = layer.activation.code();
+            bytes.extend_from_slice(&(layer.neurons as u32).to_le_bytes());
+            bytes.push(code);
+            bytes.extend_from_slice(&param.to_le_bytes());
+            bytes.push(layer.kind.code());
+        }
+        bytes.extend_from_slice(&(weights.len() as u32).to_le_bytes());
+        for weight in weights {
+            bytes.extend_from_slice(&weight.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, NetworkError> {
+        if read(&mut bytes, 4)? != MAGIC {
+            return Err(NetworkError::Corrupt(String::from("magic bytes do not match")));
+        }
+        let version = read_u32(&mut bytes)?;
+
+        let layers = read_u32(&mut bytes)? as usize;
+        let mut topology = Vec::with_capacity(layers);
+        for _ in 0..layers {
+            let neurons = read_u32(&mut bytes)? as usize;
+            let code = read(&mut bytes, 1)?[0];
+            let param = read_f32(&mut bytes)?;
+            let kind = read(&mut bytes, 1)?[0];
+
+            topology.push(LayerTopology {
+                neurons,
+                activation: Activation::from_code(code, param).ok_or(NetworkError::Corrupt(format!("unknown activation code {code}")))?,
+                kind: LayerKind::from_code(kind).ok_or(NetworkError::Corrupt(format!("unknown layer kind {kind}")))?,
+            });
+        }
+
+        let count = read_u32(&mut bytes)? as usize;
+        let weights = (0..count).map(|_| read_f32(&mut bytes)).collect::<Result<Vec<f32>, NetworkError>>()?;
+        if !bytes.is_empty() {
+            return Err(NetworkError::Corrupt(String::from("trailing bytes after the weights")));
+        }
+
+        Network::try_from(SavedNetwork { version, topology, weights })
+    }
+}
@@ -1,66 +1,218 @@
 use rand::Rng;
+use serde::{Serialize, Deserialize};
 #[cfg(test)]
 use rand::SeedableRng;
 #[cfg(test)]
 use rand_chacha::ChaCha8Rng;
 
-#[derive(Debug, Clone)]
+pub mod neat;
+mod format;
+mod error;
+mod builder;
+mod trace;
+mod train;
+mod set;
+pub mod cppn;
+
+pub use format::FORMAT_VERSION;
+pub use error::NetworkError;
+pub use builder::{NetworkBuilder, Init};
+pub use trace::{Trace, LayerTrace};
+pub use train::{Optimizer, Trainer};
+pub use set::{SetNetwork, SetTopology, SetScratch, Pooling};
+
+/// Serializes with its topology, activations and `FORMAT_VERSION`, so it can be loaded without the config that built it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "format::SavedNetwork", try_from = "format::SavedNetwork")]
 pub struct Network{
     layers: Vec<Layer>,
 }
 
+/// Caller-owned working memory for `Network::propagate_into`, create one per agent
+/// with `Network::scratch` and reuse it between calls
+#[derive(Debug, Clone, Default)]
+pub struct Scratch {
+    front: Vec<f32>,
+    back: Vec<f32>,
+    //previous outputs of every recurrent layer, learned weight changes of every plastic layer, empty for dense layers
+    memory: Vec<Vec<f32>>,
+}
+
+/// Activation buffers for `Network::propagate_batch`, one batch × neurons matrix per side. Grows to fit the largest batch it has seen
+#[derive(Debug, Clone, Default)]
+pub struct BatchScratch {
+    front: Vec<f32>,
+    back: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct LayerTopology {
     pub neurons: usize,
+    /// Applied to the outputs of this layer, ignored for the input layer
+    pub activation: Activation,
+    /// Ignored for the input layer
+    pub kind: LayerKind,
 }
 
-#[derive(Debug, Clone)]
-struct Layer{
-    neurons: Vec<Neuron>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayerKind {
+    #[default]
+    Dense,
+    /// Elman layer, every neuron also sees the layer's own outputs from the previous `propagate`
+    Recurrent,
+    /// Hebbian layer, every `propagate` changes the incoming weights by eta * (A*pre*post + B*pre + C*post + D).
+    /// Eta per neuron and A, B, C, D per connection are evolved along with the initial weights,
+    /// the learned changes live in the `Scratch` and are dropped by `Scratch::reset`
+    Plastic,
+}
+
+//learned weights are clamped to this, so unbounded inputs can't blow a plastic layer up
+const PLASTIC_WEIGHT_LIMIT: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Activation {
+    #[default]
+    ReLU,
+    /// ReLU that lets `slope * x` through for negative inputs
+    LeakyReLU(f32),
+    Tanh,
+    Sigmoid,
+    Identity,
+    Softsign,
+    /// Periodic, for repetition in CPPNs
+    Sin,
+    /// exp(-x^2), for symmetry in CPPNs
+    Gaussian,
 }
 
 #[derive(Debug, Clone)]
-struct Neuron{
-    bias: f32,
-    weights: Vec<f32>,
+struct Layer{
+    input_size: usize,
+    output_size: usize,
+    activation: Activation,
+    kind: LayerKind,
+    //one row per neuron laid out exactly like `Network::weights()`: bias, input weights, recurrent weights
+    matrix: Vec<f32>,
 }
 
 impl Network {
-    pub fn new() -> Self{
-        todo!()
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(layers: &[LayerTopology]) -> NetworkBuilder {
+        NetworkBuilder::new(layers)
     }
 
-    pub fn propagate(&self, mut inputs: Vec<f32>) -> Vec<f32> {
-        for layer in &self.layers {
-            inputs = layer.propagate(inputs);
-        }
-        return inputs;
+    /// One-off evaluation with a fresh `Scratch`, recurrent layers start with an empty memory
+    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        let mut scratch = self.scratch();
+        let mut output = vec![0.0; self.output_size()];
+        self.propagate_into(&inputs, &mut scratch, &mut output);
+        output
     }
 
-    pub fn random(rng: &mut dyn rand::RngCore, layers: &[LayerTopology]) -> Self {
-        assert!(layers.len() > 1);
+    pub fn propagate_into(&self, input: &[f32], scratch: &mut Scratch, output: &mut [f32]) {
+        assert_eq!(input.len(), self.input_size());
+        assert_eq!(output.len(), self.output_size());
+        assert_eq!(scratch.memory.len(), self.layers.len(), "scratch belongs to a different network");
+
+        let last = self.layers.len() - 1;
+        let (mut src, mut dst) = (&mut scratch.front, &mut scratch.back);
 
-        let mut built_layers = Vec::new();
+        for (idx, (layer, memory)) in self.layers.iter().zip(scratch.memory.iter_mut()).enumerate() {
+            let layer_input: &[f32] = if idx == 0 { input } else { &src[..layer.input_size] };
 
-        for adj_layers in layers.windows(2) {
-            let input_neurons: usize = adj_layers[0].neurons;
-            let output_neurons: usize = adj_layers[1].neurons;
+            if idx == last {
+                layer.propagate(layer_input, memory, output);
+            } else {
+                layer.propagate(layer_input, memory, &mut dst[..layer.output_size]);
+                std::mem::swap(&mut src, &mut dst);
+            }
+        }
+    }
 
-            built_layers.push(Layer::random(rng, input_neurons, output_neurons));
+    /// Evaluates N networks sharing one topology on N inputs, layer by layer over the whole batch.
+    /// `inputs` and `outputs` are N row-major vectors and every network keeps its own recurrent memory in `memories`.
+    /// Every network still multiplies with its own weights, so a layer is N matrix-vector products and not one matrix product,
+    /// what the batch saves is a scratch per network: the activations of all of them share the two buffers of `buffers`.
+    /// Results are bit-identical to calling `propagate_into` per network.
+    pub fn propagate_batch(networks: &[&Network], memories: &mut [&mut Scratch], inputs: &[f32], buffers: &mut BatchScratch, outputs: &mut [f32]) {
+        assert_eq!(networks.len(), memories.len());
+        if networks.is_empty() {
+            return;
         }
 
-        Self { layers: built_layers }
+        let shape = &networks[0];
+        assert!(networks.iter().all(|network| network.same_shape(shape)), "batched networks must share a topology");
+        let batch = networks.len();
+        let (input_size, output_size) = (shape.input_size(), shape.output_size());
+        assert_eq!(inputs.len(), batch * input_size);
+        assert_eq!(outputs.len(), batch * output_size);
+
+        let width = shape.layers.iter().map(|layer| layer.output_size).max().unwrap_or(0);
+        buffers.front.resize(batch * width, 0.0);
+        buffers.back.resize(batch * width, 0.0);
+
+        let last = shape.layers.len() - 1;
+        let (mut src, mut dst) = (&mut buffers.front, &mut buffers.back);
+
+        for (idx, shape_layer) in shape.layers.iter().enumerate() {
+            let (layer_in, layer_out) = (shape_layer.input_size, shape_layer.output_size);
+
+            for (n, (network, memory)) in networks.iter().zip(memories.iter_mut()).enumerate() {
+                let layer_input: &[f32] = if idx == 0 {
+                    &inputs[(n * input_size)..((n + 1) * input_size)]
+                } else {
+                    &src[(n * layer_in)..((n + 1) * layer_in)]
+                };
+                let layer_output: &mut [f32] = if idx == last {
+                    &mut outputs[(n * output_size)..((n + 1) * output_size)]
+                } else {
+                    &mut dst[(n * layer_out)..((n + 1) * layer_out)]
+                };
+
+                network.layers[idx].propagate(layer_input, &mut memory.memory[idx], layer_output);
+            }
+
+            std::mem::swap(&mut src, &mut dst);
+        }
+    }
+
+    fn same_shape(&self, other: &Network) -> bool {
+        self.layers.len() == other.layers.len() && self.layers.iter().zip(&other.layers).all(|(a, b)| {
+            a.input_size == b.input_size && a.output_size == b.output_size && a.kind == b.kind
+        })
+    }
+
+    pub fn scratch(&self) -> Scratch {
+        let width = self.layers.iter().map(|layer| layer.output_size).max().unwrap_or(0);
+        let memory = self.layers.iter().map(|layer| vec![0.0; layer.memory_size()]).collect();
+
+        Scratch { front: vec![0.0; width], back: vec![0.0; width], memory }
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.layers[0].input_size
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.layers[self.layers.len() - 1].output_size
+    }
+
+    pub fn is_recurrent(&self) -> bool {
+        self.layers.iter().any(|layer| layer.kind == LayerKind::Recurrent)
+    }
+
+    pub fn is_plastic(&self) -> bool {
+        self.layers.iter().any(|layer| layer.kind == LayerKind::Plastic)
+    }
+
+    pub fn random(rng: &mut dyn rand::RngCore, layers: &[LayerTopology]) -> Self {
+        Self::new(layers).build(rng).unwrap_or_else(|err| panic!("{err}"))
     }
 
     pub fn weights(&self) -> Vec<f32>{
-        let mut weights = Vec::new();
+        let mut weights = Vec::with_capacity(self.layers.iter().map(|layer| layer.matrix.len()).sum());
         for layer in &self.layers {
-            for neuron in &layer.neurons {
-                weights.push(neuron.bias);
-    
-                for weight in &neuron.weights {
-                    weights.push(*weight);
-                }
-            }
+            weights.extend_from_slice(&layer.matrix);
         }
         weights
     }
@@ -70,86 +222,241 @@ impl Network {
     }
 
     pub fn from_weights(layers: &[LayerTopology], weights: impl IntoIterator<Item = f32>) -> Self {
-        assert!(layers.len() > 1);
+        Self::try_from_weights(layers, weights).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        let mut weights = weights.into_iter();
-        let layers = layers.windows(2).map(|layers| {
-                Layer::from_weights(
-                    layers[0].neurons,
-                    layers[1].neurons,
-                    &mut weights,
-                )
-            }).collect();
+    pub fn try_from_weights(layers: &[LayerTopology], weights: impl IntoIterator<Item = f32>) -> Result<Self, NetworkError> {
+        //explicit weights never touch the rng
+        Self::new(layers).with_weights(weights).build(&mut rand::rngs::mock::StepRng::new(0, 0))
+    }
 
-        if weights.next().is_some() {
-            panic!("got too many weights");
-        }
+    pub fn activations(&self) -> Vec<Activation> {
+        self.layers.iter().map(|layer| layer.activation).collect()
+    }
+
+    /// The topology this network was built from, the input layer is reported as a dense identity layer
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        let input = LayerTopology { neurons: self.input_size(), activation: Activation::Identity, kind: LayerKind::Dense };
+
+        std::iter::once(input)
+            .chain(self.layers.iter().map(|layer| LayerTopology { neurons: layer.output_size, activation: layer.activation, kind: layer.kind }))
+            .collect()
+    }
+
+    /// Length of every neuron's row in `weights()`, its bias followed by its incoming (and recurrent or plastic) weights
+    pub fn neuron_sizes(layers: &[LayerTopology]) -> Vec<usize> {
+        layers.windows(2).flat_map(|layers| {
+            std::iter::repeat_n(Layer::stride_of(layers[1].kind, layers[0].neurons, layers[1].neurons), layers[1].neurons)
+        }).collect()
+    }
 
-        Self { layers }
+    /// Number of weights `from_weights` expects for this topology
+    pub fn weight_count(layers: &[LayerTopology]) -> usize {
+        layers.windows(2).map(|layers| {
+            layers[1].neurons * Layer::stride_of(layers[1].kind, layers[0].neurons, layers[1].neurons)
+        }).sum()
     }
 }
 
-impl Layer{
-    fn propagate(&self, inputs: Vec<f32>) -> Vec<f32>{
-        let mut outputs = Vec::with_capacity(self.neurons.len());
-        for neuron in &self.neurons {
-            let output = neuron.propagate(&inputs);
-            outputs.push(output);
+impl Scratch {
+    //clears the memory of recurrent layers and the learned weights of plastic layers, call at generation boundaries
+    pub fn reset(&mut self) {
+        for memory in &mut self.memory {
+            memory.iter_mut().for_each(|x| *x = 0.0);
         }
+    }
+}
 
-        outputs
+impl Activation {
+    pub fn apply(&self, val: f32) -> f32 {
+        match *self {
+            Activation::ReLU => val.max(0.0),
+            Activation::LeakyReLU(slope) => if val > 0.0 { val } else { slope * val },
+            Activation::Tanh => val.tanh(),
+            Activation::Sigmoid => 1.0 / (1.0 + (-val).exp()),
+            Activation::Identity => val,
+            Activation::Softsign => val / (1.0 + val.abs()),
+            Activation::Sin => val.sin(),
+            Activation::Gaussian => (-val * val).exp(),
+        }
     }
 
-    pub fn random(rng: &mut dyn rand::RngCore, input_neurons: usize, output_neurons: usize) -> Self {
-        let mut neurons = Vec::with_capacity(output_neurons);
-        for _ in 0..output_neurons {
-            neurons.push(Neuron::random(rng, input_neurons));
+    /// Slope at `pre`, `post` is `apply(pre)` and saves recomputing it
+    pub fn derivative(&self, pre: f32, post: f32) -> f32 {
+        match *self {
+            Activation::ReLU => if pre > 0.0 { 1.0 } else { 0.0 },
+            Activation::LeakyReLU(slope) => if pre > 0.0 { 1.0 } else { slope },
+            Activation::Tanh => 1.0 - post * post,
+            Activation::Sigmoid => post * (1.0 - post),
+            Activation::Identity => 1.0,
+            Activation::Softsign => 1.0 / ((1.0 + pre.abs()) * (1.0 + pre.abs())),
+            Activation::Sin => pre.cos(),
+            Activation::Gaussian => -2.0 * pre * post,
         }
+    }
 
-        Self { neurons }
+    //stable numbering used by the binary and gene encodings
+    pub(crate) fn code(&self) -> (u8, f32) {
+        match *self {
+            Activation::ReLU => (0, 0.0),
+            Activation::LeakyReLU(slope) => (1, slope),
+            Activation::Tanh => (2, 0.0),
+            Activation::Sigmoid => (3, 0.0),
+            Activation::Identity => (4, 0.0),
+            Activation::Softsign => (5, 0.0),
+            Activation::Sin => (6, 0.0),
+            Activation::Gaussian => (7, 0.0),
+        }
     }
 
-    pub fn from_weights(input_size: usize, output_size: usize, weights: &mut dyn Iterator<Item = f32>) -> Self {
-        let neurons = (0..output_size).map(|_| Neuron::from_weights(input_size, weights)).collect();
-        Self { neurons }
+    pub(crate) fn from_code(code: u8, param: f32) -> Option<Self> {
+        match code {
+            0 => Some(Activation::ReLU),
+            1 => Some(Activation::LeakyReLU(param)),
+            2 => Some(Activation::Tanh),
+            3 => Some(Activation::Sigmoid),
+            4 => Some(Activation::Identity),
+            5 => Some(Activation::Softsign),
+            6 => Some(Activation::Sin),
+            7 => Some(Activation::Gaussian),
+            _ => None,
+        }
     }
 }
 
-impl Neuron{
-    fn relu(val: &f32) -> f32 {
-        return val.max(0.0);
+impl LayerKind {
+    pub(crate) fn code(&self) -> u8 {
+        match self {
+            LayerKind::Dense => 0,
+            LayerKind::Recurrent => 1,
+            LayerKind::Plastic => 2,
+        }
+    }
+
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(LayerKind::Dense),
+            1 => Some(LayerKind::Recurrent),
+            2 => Some(LayerKind::Plastic),
+            _ => None,
+        }
     }
+}
+
+impl Layer{
+    fn propagate(&self, input: &[f32], memory: &mut [f32], output: &mut [f32]) {
+        self.preactivate(input, memory, output);
+        for out in output.iter_mut() {
+            *out = self.activation.apply(*out);
+        }
 
-    fn propagate(&self, inputs: &[f32]) -> f32{
-        assert_eq!(inputs.len(), self.weights.len());
+        match self.kind {
+            LayerKind::Dense => {}
+            LayerKind::Recurrent => memory.copy_from_slice(output),
+            LayerKind::Plastic => self.hebbian_update(input, output, memory),
+        }
+    }
 
-        let mut output: f32 = 0.0;
-        for (&input, &weight) in inputs.iter().zip(&self.weights) { //faster than without zipping since outofbounds checking
-            output += input * weight;
+    //row of a plastic neuron: [bias, weights, eta, A.., B.., C.., D..], memory holds the learned change of every weight
+    fn hebbian_update(&self, input: &[f32], output: &[f32], learned: &mut [f32]) {
+        for ((row, &post), learned) in self.matrix.chunks_exact(self.stride()).zip(output).zip(learned.chunks_exact_mut(self.input_size)) {
+            let (weights, rule) = row[1..].split_at(self.input_size);
+            let eta = rule[0];
+            let (a, rest) = rule[1..].split_at(self.input_size);
+            let (b, rest) = rest.split_at(self.input_size);
+            let (c, d) = rest.split_at(self.input_size);
+
+            for (k, (&pre, delta)) in input.iter().zip(learned.iter_mut()).enumerate() {
+                let change = eta * (a[k] * pre * post + b[k] * pre + c[k] * post + d[k]);
+                *delta = (weights[k] + *delta + change).clamp(-PLASTIC_WEIGHT_LIMIT, PLASTIC_WEIGHT_LIMIT) - weights[k];
+            }
         }
-        output += self.bias;
+    }
+
+    //weighted sums of every neuron before the activation is applied
+    fn preactivate(&self, input: &[f32], memory: &[f32], output: &mut [f32]) {
+        assert_eq!(input.len(), self.input_size);
+
+        for (idx, (row, out)) in self.matrix.chunks_exact(self.stride()).zip(output.iter_mut()).enumerate() {
+            let (weights, recurrent_weights) = row[1..].split_at(self.input_size);
+
+            let mut sum: f32 = 0.0;
+            for (&input, &weight) in input.iter().zip(weights) { //faster than without zipping since outofbounds checking
+                sum += input * weight;
+            }
+            if self.kind == LayerKind::Plastic {
+                let learned = &memory[(idx * self.input_size)..((idx + 1) * self.input_size)];
+                for (&input, &delta) in input.iter().zip(learned) {
+                    sum += input * delta;
+                }
+            }
+            sum += row[0];
 
-        return Self::relu(&output);
+            if self.kind == LayerKind::Recurrent {
+                let mut recurrent_sum: f32 = 0.0;
+                for (&prev, &weight) in memory.iter().zip(recurrent_weights) {
+                    recurrent_sum += prev * weight;
+                }
+                sum += recurrent_sum;
+            }
+
+            *out = sum;
+        }
     }
 
-    pub fn random(rng: &mut dyn rand::RngCore, output_size: usize) -> Self {
-        let bias = rng.gen_range(-1.0..=1.0);
+    fn recurrent_size(&self) -> usize {
+        match self.kind {
+            LayerKind::Dense | LayerKind::Plastic => 0,
+            LayerKind::Recurrent => self.output_size,
+        }
+    }
 
-        let weights = (0..output_size)
-            .map(|_| rng.gen_range(-1.0..=1.0))
-            .collect();
+    //length of the layer's slot in a Scratch
+    fn memory_size(&self) -> usize {
+        match self.kind {
+            LayerKind::Dense => 0,
+            LayerKind::Recurrent => self.output_size,
+            LayerKind::Plastic => self.output_size * self.input_size,
+        }
+    }
 
-        Self { bias, weights }
+    //length of one neuron's row
+    fn stride(&self) -> usize {
+        Self::stride_of(self.kind, self.input_size, self.output_size)
     }
 
-    pub fn from_weights(output_neurons: usize, weights: &mut dyn Iterator<Item = f32>) -> Self {
-        let bias = weights.next().expect("got not enough weights");
+    fn stride_of(kind: LayerKind, input_size: usize, output_size: usize) -> usize {
+        match kind {
+            LayerKind::Dense => 1 + input_size,
+            LayerKind::Recurrent => 1 + input_size + output_size,
+            LayerKind::Plastic => 1 + input_size + 1 + 4 * input_size,
+        }
+    }
+
+    pub fn initialized(rng: &mut dyn rand::RngCore, input_size: usize, output_size: usize, activation: Activation, kind: LayerKind, init: Init, zero_bias: bool) -> Self {
+        let mut layer = Self { input_size, output_size, activation, kind, matrix: Vec::new() };
+        let stride = layer.stride();
+        let fan_in = input_size + layer.recurrent_size();
+        //zeroed biases draw nothing, so the other weights don't depend on zero_bias.
+        //the Hebbian rule after the weights of a plastic row isn't fan-in scaled, it keeps uniform(-1, 1)
+        layer.matrix = (0..(output_size * stride))
+            .map(|idx| match idx % stride {
+                0 if zero_bias => 0.0,
+                position if position > input_size + layer.recurrent_size() => Init::default().sample(rng, fan_in, output_size),
+                _ => init.sample(rng, fan_in, output_size),
+            })
+            .collect();
+
+        layer
+    }
 
-        let weights = (0..output_neurons)
+    pub fn from_weights(input_size: usize, output_size: usize, activation: Activation, kind: LayerKind, weights: &mut dyn Iterator<Item = f32>) -> Self {
+        let mut layer = Self { input_size, output_size, activation, kind, matrix: Vec::new() };
+        layer.matrix = (0..(output_size * layer.stride()))
             .map(|_| weights.next().expect("got not enough weights"))
             .collect();
 
-        Self { bias, weights }
+        layer
     }
 }
 
@@ -158,31 +465,395 @@ fn test() {
     // Because we always use the same seed, our `rng` in here will
     // always return the same set of values
     let mut rng = ChaCha8Rng::from_seed(Default::default());
-    let neuron = Neuron::random(&mut rng, 4);
+    let layer = Layer::initialized(&mut rng, 4, 1, Activation::ReLU, LayerKind::Dense, Init::default(), false);
 
-    assert_eq!(neuron.bias, -0.6255188);
-    assert_eq!(neuron.weights, &[0.67383957, 0.8181262, 0.26284897, 0.5238807]);
+    assert_eq!(layer.matrix[0], -0.6255188);
+    assert_eq!(&layer.matrix[1..], &[0.67383957, 0.8181262, 0.26284897, 0.5238807]);
 }
 
 #[test]
 fn test2() {
-    let neuron = Neuron {
-        bias: 0.5,
-        weights: vec![-0.3, 0.8],
+    let mut layer = Layer {
+        input_size: 2,
+        output_size: 1,
+        activation: Activation::ReLU,
+        kind: LayerKind::Dense,
+        matrix: vec![0.5, -0.3, 0.8],
     };
+    let mut output = [0.0];
 
     // Ensures `.max()` (our ReLU) works:
-    approx::assert_relative_eq!(
-        neuron.propagate(&[-10.0, -10.0]),
-        0.0,
-    );
+    layer.propagate(&[-10.0, -10.0], &mut [], &mut output);
+    approx::assert_relative_eq!(output[0], 0.0);
 
     // `0.5` and `1.0` chosen by a fair dice roll:
+    layer.propagate(&[0.5, 1.0], &mut [], &mut output);
     approx::assert_relative_eq!(
-        neuron.propagate(&[0.5, 1.0]),
+        output[0],
         (-0.3 * 0.5) + (0.8 * 1.0) + 0.5,
     );
 
+    layer.activation = Activation::Identity;
+    layer.propagate(&[-10.0, -10.0], &mut [], &mut output);
+    approx::assert_relative_eq!(output[0], (-0.3 * -10.0) + (0.8 * -10.0) + 0.5);
+
     // We could've written `1.15` right away, but showing the entire
     // formula makes our intentions clearer
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_activations() {
+    approx::assert_relative_eq!(Activation::LeakyReLU(0.01).apply(-2.0), -0.02);
+    approx::assert_relative_eq!(Activation::Tanh.apply(0.5), 0.5f32.tanh());
+    approx::assert_relative_eq!(Activation::Sigmoid.apply(0.0), 0.5);
+    approx::assert_relative_eq!(Activation::Identity.apply(-3.0), -3.0);
+    approx::assert_relative_eq!(Activation::Softsign.apply(-1.0), -0.5);
+    approx::assert_relative_eq!(Activation::Sin.apply(std::f32::consts::FRAC_PI_2), 1.0);
+    approx::assert_relative_eq!(Activation::Gaussian.apply(0.0), 1.0);
+    approx::assert_relative_eq!(Activation::Gaussian.apply(1.0), (-1.0f32).exp());
+}
+
+#[test]
+fn test_activation_round_trip() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = [
+        LayerTopology { neurons: 3, activation: Activation::ReLU, kind: LayerKind::Dense },
+        LayerTopology { neurons: 4, activation: Activation::LeakyReLU(0.1), kind: LayerKind::Dense },
+        LayerTopology { neurons: 2, activation: Activation::Tanh, kind: LayerKind::Dense },
+    ];
+    let network = Network::random(&mut rng, &topology);
+    let loaded = Network::from_weights(&topology, network.weights());
+
+    assert_eq!(loaded.activations(), vec![Activation::LeakyReLU(0.1), Activation::Tanh]);
+    assert_eq!(network.propagate(vec![0.3, -0.7, 1.0]), loaded.propagate(vec![0.3, -0.7, 1.0]));
+    assert!(network.propagate(vec![-5.0, 5.0, -5.0]).iter().all(|x| x.abs() < 1.0));
+}
+
+#[test]
+fn test_recurrent_memory() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = [
+        LayerTopology { neurons: 2, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 3, activation: Activation::Tanh, kind: LayerKind::Recurrent },
+        LayerTopology { neurons: 1, activation: Activation::Identity, kind: LayerKind::Dense },
+    ];
+    let network = Network::random(&mut rng, &topology);
+    assert!(network.is_recurrent());
+    // bias + 2 inputs + 3 recurrent per hidden neuron, bias + 3 inputs for the output
+    assert_eq!(network.weights().len(), 3 * 6 + 4);
+
+    let mut scratch = network.scratch();
+    let mut first = [0.0];
+    let mut second = [0.0];
+    network.propagate_into(&[1.0, -1.0], &mut scratch, &mut first);
+    network.propagate_into(&[1.0, -1.0], &mut scratch, &mut second);
+    assert_ne!(first, second);
+
+    scratch.reset();
+    network.propagate_into(&[1.0, -1.0], &mut scratch, &mut second);
+    assert_eq!(first, second);
+
+    let loaded = Network::from_weights(&topology, network.weights());
+    assert_eq!(loaded.propagate(vec![1.0, -1.0]), first);
+}
+
+#[test]
+fn test_weights_layout() {
+    // [bias, weights...] per neuron, layer after layer, like the save files expect
+    let topology = [
+        LayerTopology { neurons: 2, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 2, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 1, activation: Activation::Identity, kind: LayerKind::Dense },
+    ];
+    let weights = vec![0.1, 0.2, 0.3, -0.4, 0.5, 0.6, 0.7, -0.8, 0.9];
+    let network = Network::from_weights(&topology, weights.clone());
+    assert_eq!(network.weights(), weights);
+    assert_eq!(Network::neuron_sizes(&topology), [3, 3, 3]);
+
+    let (x, y) = (0.25f32, -1.5f32);
+    let h1 = x * 0.2 + y * 0.3 + 0.1;
+    let h2 = x * 0.5 + y * 0.6 + -0.4;
+    let expected = h1 * -0.8 + h2 * 0.9 + 0.7;
+    assert_eq!(network.propagate(vec![x, y]), vec![expected]);
+
+    let mut scratch = network.scratch();
+    let mut output = [0.0];
+    for _ in 0..3 {
+        network.propagate_into(&[x, y], &mut scratch, &mut output);
+        assert_eq!(output, [expected]);
+    }
+}
+
+#[test]
+fn test_batch_matches_single() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = [
+        LayerTopology { neurons: 3, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 5, activation: Activation::ReLU, kind: LayerKind::Recurrent },
+        LayerTopology { neurons: 2, activation: Activation::Tanh, kind: LayerKind::Dense },
+    ];
+    let networks: Vec<Network> = (0..4).map(|_| Network::random(&mut rng, &topology)).collect();
+    let inputs: Vec<f32> = (0..12).map(|_| rng.gen_range(-1.0..=1.0)).collect();
+
+    let mut single_scratches: Vec<Scratch> = networks.iter().map(|network| network.scratch()).collect();
+    let mut batch_scratches: Vec<Scratch> = networks.iter().map(|network| network.scratch()).collect();
+    let mut buffers = BatchScratch::default();
+
+    for _ in 0..3 { // repeated so recurrent memory is exercised too
+        let mut expected = vec![0.0; 8];
+        for (n, (network, scratch)) in networks.iter().zip(&mut single_scratches).enumerate() {
+            network.propagate_into(&inputs[(n * 3)..((n + 1) * 3)], scratch, &mut expected[(n * 2)..((n + 1) * 2)]);
+        }
+
+        let refs: Vec<&Network> = networks.iter().collect();
+        let mut memories: Vec<&mut Scratch> = batch_scratches.iter_mut().collect();
+        let mut outputs = vec![0.0; 8];
+        Network::propagate_batch(&refs, &mut memories, &inputs, &mut buffers, &mut outputs);
+
+        assert_eq!(outputs, expected);
+    }
+}
+
+#[test]
+fn test_serialization() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = [
+        LayerTopology { neurons: 3, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 4, activation: Activation::LeakyReLU(0.05), kind: LayerKind::Recurrent },
+        LayerTopology { neurons: 2, activation: Activation::Softsign, kind: LayerKind::Dense },
+    ];
+    let network = Network::random(&mut rng, &topology);
+    assert_eq!(network.topology(), topology);
+    assert_eq!(Network::weight_count(&topology), network.weights().len());
+
+    let json = serde_json::to_string(&network).unwrap();
+    let from_json: Network = serde_json::from_str(&json).unwrap();
+    assert_eq!(from_json.topology(), topology);
+    assert_eq!(from_json.weights(), network.weights());
+
+    let from_bytes = Network::from_bytes(&network.to_bytes()).unwrap();
+    assert_eq!(from_bytes.topology(), topology);
+    assert_eq!(from_bytes.weights(), network.weights());
+
+    let mut truncated = network.to_bytes();
+    truncated.pop();
+    assert!(Network::from_bytes(&truncated).is_err());
+
+    let wrong_version = json.replace(&format!("\"version\":{FORMAT_VERSION}"), "\"version\":999");
+    assert!(serde_json::from_str::<Network>(&wrong_version).is_err());
+}
+
+#[test]
+fn test_builder_errors() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = [
+        LayerTopology { neurons: 2, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 1, activation: Activation::Tanh, kind: LayerKind::Dense },
+    ];
+
+    assert_eq!(Network::new(&topology[..1]).build(&mut rng).unwrap_err(), NetworkError::EmptyTopology);
+    let zero_width = [topology[0], LayerTopology { neurons: 0, ..topology[1] }];
+    assert_eq!(Network::new(&zero_width).build(&mut rng).unwrap_err(), NetworkError::ZeroWidthLayer(1));
+    assert_eq!(
+        Network::try_from_weights(&topology, vec![0.1, 0.2]).unwrap_err(),
+        NetworkError::WrongWeightCount { expected: 3, got: 2 },
+    );
+    assert_eq!(
+        Network::try_from_weights(&topology, vec![0.1; 4]).unwrap_err(),
+        NetworkError::WrongWeightCount { expected: 3, got: 4 },
+    );
+
+    let network = Network::new(&topology).with_weights(vec![0.1, 0.2, 0.3]).build(&mut rng).unwrap();
+    assert_eq!(network.weights(), vec![0.1, 0.2, 0.3]);
+    let zeros = Network::new(&topology).with_init(Init::Zeros).build(&mut rng).unwrap();
+    assert_eq!(zeros.weights(), vec![0.0; 3]);
+    let uniform = Network::new(&topology).with_init(Init::Uniform(0.1)).build(&mut rng).unwrap();
+    assert!(uniform.weights().iter().all(|w| w.abs() <= 0.1));
+}
+
+#[test]
+fn test_init_schemes() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = [
+        LayerTopology { neurons: 200, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 100, activation: Activation::ReLU, kind: LayerKind::Dense },
+    ];
+    let std = |network: &Network| {
+        let weights: Vec<f32> = network.weights().chunks(201).flat_map(|row| row[1..].to_vec()).collect();
+        (weights.iter().map(|w| w * w).sum::<f32>() / weights.len() as f32).sqrt()
+    };
+
+    let he = Network::new(&topology).with_init(Init::He).with_zero_bias(true).build(&mut rng).unwrap();
+    assert!(he.weights().chunks(201).all(|row| row[0] == 0.0));
+    approx::assert_relative_eq!(std(&he), (2.0f32 / 200.0).sqrt(), max_relative = 0.05);
+
+    let xavier = Network::new(&topology).with_init(Init::Xavier).build(&mut rng).unwrap();
+    let limit = (6.0f32 / 300.0).sqrt();
+    assert!(xavier.weights().iter().all(|w| w.abs() <= limit));
+    approx::assert_relative_eq!(std(&xavier), limit / 3.0f32.sqrt(), max_relative = 0.05);
+
+    let scaled = Network::new(&topology).with_init(Init::ScaledNormal(0.5)).build(&mut rng).unwrap();
+    approx::assert_relative_eq!(std(&scaled), 0.5 / 200.0f32.sqrt(), max_relative = 0.05);
+
+    //only the weights of a plastic layer follow the scheme, its Hebbian rule keeps uniform(-1, 1)
+    let topology = [
+        LayerTopology { neurons: 200, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 10, activation: Activation::Identity, kind: LayerKind::Plastic },
+    ];
+    let plastic = Network::new(&topology).with_init(Init::Zeros).build(&mut rng).unwrap();
+    for row in plastic.weights().chunks(1 + 200 + 1 + 4 * 200) {
+        assert!(row[..201].iter().all(|&w| w == 0.0));
+        assert!(row[201..].iter().all(|w| (-1.0..=1.0).contains(w)));
+        assert!(row[201..].iter().any(|&w| w != 0.0));
+    }
+}
+
+#[test]
+fn test_propagate_traced() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = [
+        LayerTopology { neurons: 3, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 4, activation: Activation::ReLU, kind: LayerKind::Recurrent },
+        LayerTopology { neurons: 2, activation: Activation::Tanh, kind: LayerKind::Dense },
+    ];
+    let network = Network::random(&mut rng, &topology);
+    let (mut traced, mut plain) = (network.scratch(), network.scratch());
+    let mut output = vec![0.0; 2];
+
+    for input in [[0.2, -0.4, 0.9], [-1.0, 0.5, 0.3]] {
+        let trace = network.propagate_traced(&input, &mut traced);
+        network.propagate_into(&input, &mut plain, &mut output);
+
+        assert_eq!(trace.input, input);
+        assert_eq!(trace.output(), &output[..]);
+        assert_eq!(trace.layers.len(), 2);
+        assert_eq!(trace.layers[1].input, trace.layers[0].post);
+        for layer in &trace.layers {
+            let activated: Vec<f32> = layer.pre.iter().map(|&x| layer.activation.apply(x)).collect();
+            assert_eq!(activated, layer.post);
+        }
+    }
+}
+
+#[test]
+fn test_backpropagation() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = [
+        LayerTopology { neurons: 2, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 8, activation: Activation::Tanh, kind: LayerKind::Dense },
+        LayerTopology { neurons: 1, activation: Activation::Identity, kind: LayerKind::Dense },
+    ];
+    let samples: Vec<(Vec<f32>, Vec<f32>)> = (0..64).map(|i| {
+        let (x, y) = ((i % 8) as f32 / 4.0 - 1.0, (i / 8) as f32 / 4.0 - 1.0);
+        (vec![x, y], vec![0.5 * x - y])
+    }).collect();
+
+    for optimizer in [Optimizer::Sgd { learning_rate: 0.05, momentum: 0.9 }, Optimizer::adam(0.01)] {
+        let mut network = Network::new(&topology).with_init(Init::Xavier).build(&mut rng).unwrap();
+        let mut trainer = Trainer::new(&network, optimizer).unwrap();
+        let losses = trainer.fit(&mut rng, &mut network, &samples, 200, 8).unwrap();
+
+        assert!(losses[losses.len() - 1] < losses[0] * 0.01, "{optimizer:?}: {losses:?}");
+    }
+
+    let recurrent = [topology[0], LayerTopology { kind: LayerKind::Recurrent, ..topology[1] }, topology[2]];
+    let network = Network::random(&mut rng, &recurrent);
+    assert_eq!(Trainer::new(&network, Optimizer::sgd(0.1)).unwrap_err(), NetworkError::NotTrainable(1));
+}
+
+#[test]
+fn test_plastic_layer() {
+    let topology = [
+        LayerTopology { neurons: 2, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 1, activation: Activation::Identity, kind: LayerKind::Plastic },
+    ];
+    assert_eq!(Network::weight_count(&topology), 12);
+
+    //bias 0, weights [0.5, -0.5], eta 0.1, pure Hebbian on the first input, constant decay on the second
+    let network = Network::from_weights(&topology, vec![0.0, 0.5, -0.5, 0.1, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0]);
+    let mut scratch = network.scratch();
+    let mut output = vec![0.0];
+
+    network.propagate_into(&[1.0, 1.0], &mut scratch, &mut output);
+    assert_eq!(output, vec![0.0]);
+    network.propagate_into(&[2.0, 1.0], &mut scratch, &mut output);
+    approx::assert_relative_eq!(output[0], 2.0 * 0.5 + 1.0 * (-0.5 - 0.1));
+    let post = output[0];
+    network.propagate_into(&[1.0, 0.0], &mut scratch, &mut output);
+    approx::assert_relative_eq!(output[0], 0.5 + 0.1 * 2.0 * post);
+
+    scratch.reset();
+    network.propagate_into(&[1.0, 1.0], &mut scratch, &mut output);
+    assert_eq!(output, vec![0.0]);
+}
+
+#[test]
+fn test_set_network() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let topology = SetTopology {
+        entity_size: 3,
+        context_size: 1,
+        presence: Some(2),
+        pooling: Pooling::Sum,
+        encoder: vec![
+            LayerTopology { neurons: 3, activation: Activation::Identity, kind: LayerKind::Dense },
+            LayerTopology { neurons: 4, activation: Activation::Tanh, kind: LayerKind::Dense },
+        ],
+        head: vec![
+            LayerTopology { neurons: 5, activation: Activation::Identity, kind: LayerKind::Dense },
+            LayerTopology { neurons: 2, activation: Activation::Tanh, kind: LayerKind::Dense },
+        ],
+    };
+    let network = SetNetwork::random(&mut rng, &topology, Init::default(), false).unwrap();
+    let mut scratch = network.scratch();
+    let run = |input: &[f32], scratch: &mut SetScratch| {
+        let mut output = vec![0.0; 2];
+        network.propagate_into(input, scratch, &mut output);
+        output
+    };
+
+    let (a, b, hidden) = ([0.1, -0.2, 1.0], [0.4, 0.3, 1.0], [0.9, 0.9, 0.0]);
+    let forward = run(&[a, b, hidden].concat().into_iter().chain([0.5]).collect::<Vec<f32>>(), &mut scratch);
+    let backward = run(&[b, a].concat().into_iter().chain([0.5]).collect::<Vec<f32>>(), &mut scratch);
+    for (x, y) in forward.iter().zip(&backward) {
+        approx::assert_relative_eq!(x, y, epsilon = 1e-6);
+    }
+    assert_eq!(run(&[0.5], &mut scratch), run(&[hidden.to_vec(), vec![0.5]].concat(), &mut scratch));
+
+    let loaded = SetNetwork::try_from_weights(&topology, network.weights()).unwrap();
+    assert_eq!(loaded.weights(), network.weights());
+    assert_eq!(SetNetwork::try_from_weights(&topology, vec![0.0; 3]).unwrap_err(), NetworkError::WrongWeightCount { expected: topology.weight_count(), got: 3 });
+    let bad_head = SetTopology { context_size: 2, ..topology.clone() };
+    assert!(matches!(bad_head.validate(), Err(NetworkError::SetShape(_))));
+}
+
+#[test]
+fn test_cppn_network() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let mut cppn = cppn::minimal(&mut rng);
+    let mut tracker = neat::InnovationTracker::new(cppn::CPPN_INPUTS, cppn::CPPN_OUTPUTS);
+    for _ in 0..5 {
+        cppn.mutate_add_node(&mut rng, &mut tracker);
+        cppn.mutate_activation(&mut rng, &cppn::CPPN_ACTIVATIONS);
+    }
+
+    let small = [
+        LayerTopology { neurons: 4, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 2, activation: Activation::Tanh, kind: LayerKind::Dense },
+    ];
+    let large = [
+        LayerTopology { neurons: 64, activation: Activation::Identity, kind: LayerKind::Dense },
+        LayerTopology { neurons: 32, activation: Activation::ReLU, kind: LayerKind::Recurrent },
+        LayerTopology { neurons: 2, activation: Activation::Tanh, kind: LayerKind::Dense },
+    ];
+
+    //the genome stays the same size whatever network it paints
+    let genes = cppn.to_genes().len();
+    let network = Network::from_cppn(&cppn.network(), &large).unwrap();
+    assert_eq!(network.weights().len(), Network::weight_count(&large));
+    assert!(genes < network.weights().len() / 10);
+    assert!(network.weights().iter().all(|w| w.abs() <= 3.0));
+    assert!(network.weights().iter().any(|&w| w != 0.0));
+    assert_eq!(Network::from_cppn(&cppn.network(), &small).unwrap().weights().len(), Network::weight_count(&small));
+    assert_eq!(Network::from_cppn(&cppn.network(), &small[..1]).unwrap_err(), NetworkError::EmptyTopology);
+}
@@ -0,0 +1,513 @@
+use crate::*;
+use std::collections::HashMap;
+#[cfg(test)]
+use rand::SeedableRng;
+#[cfg(test)]
+use rand_chacha::ChaCha8Rng;
+
+//flat gene layout, see Genome::to_genes
+const HEADER_LEN: usize = 6;
+const NODE_LEN: usize = 4;
+const CONNECTION_LEN: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Input,
+    Hidden,
+    Output,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeGene {
+    pub id: usize,
+    pub kind: NodeKind,
+    pub bias: f32,
+    pub activation: Activation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionGene {
+    pub innovation: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+    pub enabled: bool,
+}
+
+/// NEAT genome, node ids `0..inputs` are the inputs and `inputs..(inputs + outputs)` the outputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Genome {
+    inputs: usize,
+    outputs: usize,
+    hidden_activation: Activation,
+    nodes: Vec<NodeGene>,
+    connections: Vec<ConnectionGene>, //sorted by innovation
+}
+
+/// Hands out innovation numbers and node ids, the same structural mutation always gets the same numbers
+#[derive(Debug, Clone, Default)]
+pub struct InnovationTracker {
+    next_innovation: usize,
+    next_node: usize,
+    connections: HashMap<(usize, usize), usize>,
+    splits: HashMap<usize, usize>, //split connection innovation -> node id
+}
+
+/// Executable form of a `Genome`, nodes are evaluated in topological order
+#[derive(Debug, Clone)]
+pub struct NeatNetwork {
+    inputs: usize,
+    nodes: Vec<NeatNode>,
+    order: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct NeatNode {
+    bias: f32,
+    activation: Activation,
+    incoming: Vec<(usize, f32)>,
+}
+
+impl Genome {
+    /// Every input connected to every output, innovation numbers are the same for all minimal genomes of this shape
+    pub fn minimal(rng: &mut dyn rand::RngCore, inputs: usize, outputs: usize, hidden_activation: Activation, output_activation: Activation) -> Self {
+        assert!(inputs > 0 && outputs > 0);
+
+        let mut nodes = Vec::with_capacity(inputs + outputs);
+        for id in 0..inputs {
+            nodes.push(NodeGene { id, kind: NodeKind::Input, bias: 0.0, activation: Activation::Identity });
+        }
+        for id in inputs..(inputs + outputs) {
+            nodes.push(NodeGene { id, kind: NodeKind::Output, bias: rng.gen_range(-1.0..=1.0), activation: output_activation });
+        }
+
+        let mut connections = Vec::with_capacity(inputs * outputs);
+        for from in 0..inputs {
+            for out in 0..outputs {
+                connections.push(ConnectionGene {
+                    innovation: from * outputs + out,
+                    from,
+                    to: inputs + out,
+                    weight: rng.gen_range(-1.0..=1.0),
+                    enabled: true,
+                });
+            }
+        }
+
+        Self { inputs, outputs, hidden_activation, nodes, connections }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    pub fn nodes(&self) -> &[NodeGene] {
+        &self.nodes
+    }
+
+    pub fn connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    fn node(&self, id: usize) -> Option<&NodeGene> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    //true if `to` can already be reached from `from`, connecting to -> from would create a cycle
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut visited = vec![from];
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            for connection in self.connections.iter().filter(|c| c.from == node) {
+                if !visited.contains(&connection.to) {
+                    visited.push(connection.to);
+                    stack.push(connection.to);
+                }
+            }
+        }
+        false
+    }
+
+    fn insert_connection(&mut self, connection: ConnectionGene) {
+        let idx = self.connections.partition_point(|c| c.innovation < connection.innovation);
+        self.connections.insert(idx, connection);
+    }
+
+    /// Connects two unconnected nodes, gives up after `attempts` random picks that would duplicate a connection or form a cycle
+    pub fn mutate_add_connection(&mut self, rng: &mut dyn rand::RngCore, tracker: &mut InnovationTracker, attempts: usize) -> bool {
+        let sources: Vec<usize> = self.nodes.iter().filter(|n| n.kind != NodeKind::Output).map(|n| n.id).collect();
+        let targets: Vec<usize> = self.nodes.iter().filter(|n| n.kind != NodeKind::Input).map(|n| n.id).collect();
+
+        for _ in 0..attempts {
+            let from = sources[rng.gen_range(0..sources.len())];
+            let to = targets[rng.gen_range(0..targets.len())];
+
+            if from == to || self.connections.iter().any(|c| c.from == from && c.to == to) || self.reaches(to, from) {
+                continue;
+            }
+
+            let innovation = tracker.connection(from, to);
+            self.insert_connection(ConnectionGene { innovation, from, to, weight: rng.gen_range(-1.0..=1.0), enabled: true });
+            return true;
+        }
+
+        false
+    }
+
+    /// Splits a random enabled connection a -> b into a -> new -> b, the old connection gets disabled
+    pub fn mutate_add_node(&mut self, rng: &mut dyn rand::RngCore, tracker: &mut InnovationTracker) -> bool {
+        let enabled: Vec<usize> = (0..self.connections.len()).filter(|&i| self.connections[i].enabled).collect();
+        if enabled.is_empty() {
+            return false;
+        }
+
+        let idx = enabled[rng.gen_range(0..enabled.len())];
+        let (innovation, from, to, weight) = {
+            let split = &mut self.connections[idx];
+            split.enabled = false;
+            (split.innovation, split.from, split.to, split.weight)
+        };
+
+        let id = tracker.split(innovation);
+        if self.node(id).is_some() { //this genome already split the connection once
+            self.connections[idx].enabled = true;
+            return false;
+        }
+
+        self.nodes.push(NodeGene { id, kind: NodeKind::Hidden, bias: 0.0, activation: self.hidden_activation });
+        let incoming = tracker.connection(from, id);
+        let outgoing = tracker.connection(id, to);
+        self.insert_connection(ConnectionGene { innovation: incoming, from, to: id, weight: 1.0, enabled: true });
+        self.insert_connection(ConnectionGene { innovation: outgoing, from: id, to, weight, enabled: true });
+
+        true
+    }
+
+    /// Gives a random hidden node one of `choices` as its activation
+    pub fn mutate_activation(&mut self, rng: &mut dyn rand::RngCore, choices: &[Activation]) -> bool {
+        let hidden: Vec<usize> = (0..self.nodes.len()).filter(|&i| self.nodes[i].kind == NodeKind::Hidden).collect();
+        if hidden.is_empty() || choices.is_empty() {
+            return false;
+        }
+
+        let idx = hidden[rng.gen_range(0..hidden.len())];
+        self.nodes[idx].activation = choices[rng.gen_range(0..choices.len())];
+        true
+    }
+
+    pub fn mutate_weights(&mut self, rng: &mut dyn rand::RngCore, chance: f32, coeff: f32) {
+        let genes = self.connections.iter_mut().map(|c| &mut c.weight)
+            .chain(self.nodes.iter_mut().filter(|n| n.kind != NodeKind::Input).map(|n| &mut n.bias));
+
+        //gaussian like the GA's weight mutation, coeff is the standard deviation
+        for gene in genes {
+            if rng.gen_bool(chance as _) {
+                *gene += coeff * rng.sample::<f32, _>(rand_distr::StandardNormal);
+            }
+        }
+    }
+
+    /// Aligns the connection genes by innovation number, matching genes come from either parent
+    /// and disjoint or excess genes from `self`, which should be the fitter parent
+    pub fn crossover(&self, rng: &mut dyn rand::RngCore, other: &Genome) -> Genome {
+        assert_eq!(self.inputs, other.inputs);
+        assert_eq!(self.outputs, other.outputs);
+
+        let connections: Vec<ConnectionGene> = self.connections.iter().map(|gene| {
+            match other.connections.binary_search_by_key(&gene.innovation, |c| c.innovation) {
+                Ok(idx) => {
+                    let mut child = if rng.gen_bool(0.5) { gene.clone() } else { other.connections[idx].clone() };
+                    //a gene disabled in either parent stays disabled most of the time, like in the paper
+                    if !gene.enabled || !other.connections[idx].enabled {
+                        child.enabled = rng.gen_bool(0.25);
+                    }
+                    child
+                }
+                Err(_) => gene.clone(),
+            }
+        }).collect();
+
+        let nodes = self.nodes.iter().map(|node| {
+            match other.node(node.id) {
+                Some(other_node) if rng.gen_bool(0.5) => other_node.clone(),
+                _ => node.clone(),
+            }
+        }).collect();
+
+        Genome { inputs: self.inputs, outputs: self.outputs, hidden_activation: self.hidden_activation, nodes, connections }
+    }
+
+    pub fn network(&self) -> NeatNetwork {
+        let index: HashMap<usize, usize> = self.nodes.iter().enumerate().map(|(idx, node)| (node.id, idx)).collect();
+
+        let mut nodes: Vec<NeatNode> = self.nodes.iter()
+            .map(|node| NeatNode { bias: node.bias, activation: node.activation, incoming: Vec::new() })
+            .collect();
+        for connection in self.connections.iter().filter(|c| c.enabled) {
+            nodes[index[&connection.to]].incoming.push((index[&connection.from], connection.weight));
+        }
+
+        //kahn's algorithm, the genome is acyclic by construction
+        let mut pending: Vec<usize> = nodes.iter().map(|node| node.incoming.len()).collect();
+        let mut ready: Vec<usize> = (0..nodes.len()).filter(|&idx| pending[idx] == 0).collect();
+        let mut order = Vec::with_capacity(nodes.len());
+        while let Some(idx) = ready.pop() {
+            if self.nodes[idx].kind != NodeKind::Input {
+                order.push(idx);
+            }
+            for (target, node) in nodes.iter().enumerate() {
+                for _ in node.incoming.iter().filter(|(source, _)| *source == idx) {
+                    pending[target] -= 1;
+                    if pending[target] == 0 {
+                        ready.push(target);
+                    }
+                }
+            }
+        }
+
+        let outputs = (self.inputs..(self.inputs + self.outputs)).map(|id| index[&id]).collect();
+
+        NeatNetwork { inputs: self.inputs, nodes, order, outputs }
+    }
+
+    /// Flat encoding used to carry genomes inside a `Chromosome`:
+    /// header `[inputs, outputs, activation code, activation param, node count, connection count]`,
+    /// then `[id, bias, activation code, activation param]` per non-input node
+    /// and `[innovation, from, to, weight, enabled]` per connection
+    pub fn to_genes(&self) -> Vec<f32> {
+        let hidden = self.nodes.iter().filter(|node| node.kind != NodeKind::Input);
+        let (code, param) = activation_to_genes(self.hidden_activation);
+
+        let mut genes = vec![self.inputs as f32, self.outputs as f32, code, param, hidden.clone().count() as f32, self.connections.len() as f32];
+        for node in hidden {
+            let (code, param) = activation_to_genes(node.activation);
+            genes.extend_from_slice(&[node.id as f32, node.bias, code, param]);
+        }
+        for connection in &self.connections {
+            genes.extend_from_slice(&[
+                connection.innovation as f32,
+                connection.from as f32,
+                connection.to as f32,
+                connection.weight,
+                if connection.enabled { 1.0 } else { 0.0 },
+            ]);
+        }
+
+        genes
+    }
+
+    pub fn from_genes(genes: &[f32]) -> Self {
+        Self::try_from_genes(genes).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_from_genes(genes: &[f32]) -> Result<Self, NetworkError> {
+        if genes.len() < HEADER_LEN {
+            return Err(NetworkError::Corrupt(String::from("got not enough genes")));
+        }
+        let inputs = genes[0] as usize;
+        let outputs = genes[1] as usize;
+        if inputs == 0 || outputs == 0 {
+            return Err(NetworkError::EmptyTopology);
+        }
+        let hidden_activation = activation_from_genes(genes[2], genes[3])?;
+        let node_count = genes[4] as usize;
+        let connection_count = genes[5] as usize;
+        let expected = HEADER_LEN + node_count * NODE_LEN + connection_count * CONNECTION_LEN;
+        if genes.len() != expected {
+            return Err(NetworkError::WrongWeightCount { expected, got: genes.len() });
+        }
+
+        let mut nodes: Vec<NodeGene> = (0..inputs)
+            .map(|id| NodeGene { id, kind: NodeKind::Input, bias: 0.0, activation: Activation::Identity })
+            .collect();
+        let node_genes = &genes[HEADER_LEN..(HEADER_LEN + node_count * NODE_LEN)];
+        for gene in node_genes.chunks_exact(NODE_LEN) {
+            let id = gene[0] as usize;
+            let kind = if id < inputs + outputs { NodeKind::Output } else { NodeKind::Hidden };
+            nodes.push(NodeGene { id, kind, bias: gene[1], activation: activation_from_genes(gene[2], gene[3])? });
+        }
+
+        let connections: Vec<ConnectionGene> = genes[(HEADER_LEN + node_count * NODE_LEN)..].chunks_exact(CONNECTION_LEN).map(|gene| {
+            ConnectionGene {
+                innovation: gene[0] as usize,
+                from: gene[1] as usize,
+                to: gene[2] as usize,
+                weight: gene[3],
+                enabled: gene[4] > 0.5,
+            }
+        }).collect();
+
+        let known = |id: usize| nodes.iter().any(|node| node.id == id);
+        if let Some(connection) = connections.iter().find(|c| !known(c.from) || !known(c.to)) {
+            return Err(NetworkError::Corrupt(format!("connection {} references a missing node", connection.innovation)));
+        }
+
+        Ok(Self { inputs, outputs, hidden_activation, nodes, connections })
+    }
+}
+
+impl InnovationTracker {
+    /// Starts counting after the innovations `Genome::minimal` uses
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        Self {
+            next_innovation: inputs * outputs,
+            next_node: inputs + outputs,
+            ..Default::default()
+        }
+    }
+
+    /// Makes sure genomes created elsewhere (loaded, decoded from chromosomes) never collide with new numbers
+    pub fn observe(&mut self, genome: &Genome) {
+        for connection in &genome.connections {
+            self.next_innovation = self.next_innovation.max(connection.innovation + 1);
+            self.connections.entry((connection.from, connection.to)).or_insert(connection.innovation);
+        }
+        for node in &genome.nodes {
+            self.next_node = self.next_node.max(node.id + 1);
+        }
+    }
+
+    fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next = &mut self.next_innovation;
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    fn split(&mut self, innovation: usize) -> usize {
+        let next = &mut self.next_node;
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+}
+
+impl NeatNetwork {
+    pub fn input_size(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Buffer of node values for `propagate_into`
+    pub fn values(&self) -> Vec<f32> {
+        vec![0.0; self.nodes.len()]
+    }
+
+    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        let mut values = self.values();
+        let mut output = vec![0.0; self.output_size()];
+        self.propagate_into(&inputs, &mut values, &mut output);
+        output
+    }
+
+    pub fn propagate_into(&self, input: &[f32], values: &mut [f32], output: &mut [f32]) {
+        assert_eq!(input.len(), self.inputs);
+        assert_eq!(output.len(), self.outputs.len());
+
+        values[..self.inputs].copy_from_slice(input);
+        for &idx in &self.order {
+            let node = &self.nodes[idx];
+            let mut sum: f32 = 0.0;
+            for &(source, weight) in &node.incoming {
+                sum += values[source] * weight;
+            }
+            sum += node.bias;
+            values[idx] = node.activation.apply(sum);
+        }
+
+        for (out, &idx) in output.iter_mut().zip(&self.outputs) {
+            *out = values[idx];
+        }
+    }
+}
+
+fn activation_to_genes(activation: Activation) -> (f32, f32) {
+    let (code, param) = activation.code();
+    (code as f32, param)
+}
+
+fn activation_from_genes(code: f32, param: f32) -> Result<Activation, NetworkError> {
+    Activation::from_code(code as u8, param).ok_or(NetworkError::Corrupt(format!("unknown activation code {code}")))
+}
+
+#[test]
+fn test_minimal_network() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let genome = Genome::minimal(&mut rng, 2, 1, Activation::Tanh, Activation::Identity);
+    let weights: Vec<f32> = genome.connections().iter().map(|c| c.weight).collect();
+    let bias = genome.nodes()[2].bias;
+
+    let output = genome.network().propagate(vec![0.5, -2.0]);
+    assert_eq!(output, vec![0.5 * weights[0] + -2.0 * weights[1] + bias]);
+}
+
+#[test]
+fn test_structural_mutations() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let mut tracker = InnovationTracker::new(3, 2);
+    let original = Genome::minimal(&mut rng, 3, 2, Activation::Tanh, Activation::Tanh);
+
+    let mut a = original.clone();
+    assert!(a.mutate_add_node(&mut rng, &mut tracker));
+    assert_eq!(a.nodes().len(), 6);
+    assert_eq!(a.connections().iter().filter(|c| !c.enabled).count(), 1);
+    let split = a.connections().iter().find(|c| !c.enabled).unwrap().innovation;
+    assert!(a.mutate_add_connection(&mut rng, &mut tracker, 50));
+
+    // the same split in another genome gets the same node id and innovations
+    let mut b = original.clone();
+    b.mutate_add_node(&mut rng, &mut tracker);
+    while b.connections().iter().any(|c| !c.enabled && c.innovation != split) {
+        b = original.clone();
+        b.mutate_add_node(&mut rng, &mut tracker);
+    }
+    assert_eq!(a.nodes()[5].id, b.nodes()[5].id);
+    assert_eq!(a.connections().len(), b.connections().len() + 1);
+
+    let child = a.crossover(&mut rng, &b);
+    assert_eq!(child.connections().len(), a.connections().len());
+    assert!(child.connections().windows(2).all(|w| w[0].innovation < w[1].innovation));
+    assert_eq!(child.network().propagate(vec![1.0, 0.0, -1.0]).len(), 2);
+}
+
+#[test]
+fn test_genes_round_trip() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let mut tracker = InnovationTracker::new(4, 2);
+    let mut genome = Genome::minimal(&mut rng, 4, 2, Activation::LeakyReLU(0.1), Activation::Tanh);
+    for _ in 0..5 {
+        genome.mutate_add_node(&mut rng, &mut tracker);
+        genome.mutate_add_connection(&mut rng, &mut tracker, 20);
+    }
+
+    let decoded = Genome::from_genes(&genome.to_genes());
+    assert_eq!(decoded, genome);
+    assert_eq!(decoded.network().propagate(vec![0.1, 0.2, 0.3, 0.4]), genome.network().propagate(vec![0.1, 0.2, 0.3, 0.4]));
+}
+
+#[test]
+fn test_weight_mutation() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let original = Genome::minimal(&mut rng, 50, 20, Activation::Tanh, Activation::Tanh);
+    let mut genome = original.clone();
+    genome.mutate_weights(&mut rng, 1.0, 0.5);
+
+    //normal changes with the magnitude as standard deviation, some beyond it which a uniform(0, 1) step never reaches
+    let changes: Vec<f32> = genome.connections().iter().zip(original.connections()).map(|(a, b)| a.weight - b.weight).collect();
+    let std = (changes.iter().map(|c| c * c).sum::<f32>() / changes.len() as f32).sqrt();
+    approx::assert_relative_eq!(std, 0.5, max_relative = 0.05);
+    assert!(changes.iter().any(|c| c.abs() > 0.5));
+}
@@ -0,0 +1,170 @@
+use crate::*;
+
+/// How a `SetNetwork` combines the encoded entities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Pooling {
+    /// Keeps how many entities there are
+    #[default]
+    Sum,
+    Mean,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetTopology {
+    pub entity_size: usize,
+    /// Fixed features after the entities that bypass the encoder, e.g. the time
+    pub context_size: usize,
+    /// Feature of an entity that flags it as present, entities where it is 0.0 are left out of the pool
+    pub presence: Option<usize>,
+    pub pooling: Pooling,
+    /// Runs on every entity on its own, starts with `entity_size` neurons and has to be dense
+    pub encoder: Vec<LayerTopology>,
+    /// Sees the pooled features followed by the context
+    pub head: Vec<LayerTopology>,
+}
+
+/// Deep sets network, the input is any number of entities of `entity_size` features followed by the context.
+/// Every entity goes through the same encoder and the results are pooled, so neither the number
+/// nor the order of the entities matters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetNetwork {
+    topology: SetTopology,
+    encoder: Network,
+    head: Network,
+}
+
+/// Per-agent working memory of a `SetNetwork`, the head keeps its recurrent or plastic state in here
+#[derive(Debug, Clone)]
+pub struct SetScratch {
+    encoder: Scratch,
+    head: Scratch,
+    encoded: Vec<f32>,
+    head_input: Vec<f32>,
+}
+
+impl SetTopology {
+    pub fn validate(&self) -> Result<(), NetworkError> {
+        let (Some(first), Some(last)) = (self.encoder.first(), self.encoder.last()) else {
+            return Err(NetworkError::EmptyTopology);
+        };
+        if first.neurons != self.entity_size {
+            return Err(NetworkError::SetShape("encoder input doesn't match the entity size"));
+        }
+        if self.head.first().map(|layer| layer.neurons) != Some(last.neurons + self.context_size) {
+            return Err(NetworkError::SetShape("head input has to be the encoder output plus the context"));
+        }
+        if self.presence.is_some_and(|idx| idx >= self.entity_size) {
+            return Err(NetworkError::SetShape("presence flag is not an entity feature"));
+        }
+        //entities are encoded one after another, memory would leak between them
+        if self.encoder.iter().any(|layer| layer.kind != LayerKind::Dense) {
+            return Err(NetworkError::SetShape("encoder layers have to be dense"));
+        }
+
+        Ok(())
+    }
+
+    pub fn weight_count(&self) -> usize {
+        Network::weight_count(&self.encoder) + Network::weight_count(&self.head)
+    }
+
+    /// Rows of the encoder followed by the rows of the head, see `Network::neuron_sizes`
+    pub fn neuron_sizes(&self) -> Vec<usize> {
+        let mut sizes = Network::neuron_sizes(&self.encoder);
+        sizes.extend(Network::neuron_sizes(&self.head));
+        sizes
+    }
+}
+
+impl SetNetwork {
+    pub fn random(rng: &mut dyn rand::RngCore, topology: &SetTopology, init: Init, zero_bias: bool) -> Result<Self, NetworkError> {
+        topology.validate()?;
+        let encoder = Network::new(&topology.encoder).with_init(init).with_zero_bias(zero_bias).build(rng)?;
+        let head = Network::new(&topology.head).with_init(init).with_zero_bias(zero_bias).build(rng)?;
+
+        Ok(Self { topology: topology.clone(), encoder, head })
+    }
+
+    /// Encoder weights followed by the head weights, the layout of `weights()`
+    pub fn try_from_weights(topology: &SetTopology, weights: impl IntoIterator<Item = f32>) -> Result<Self, NetworkError> {
+        topology.validate()?;
+        let weights: Vec<f32> = weights.into_iter().collect();
+        if weights.len() != topology.weight_count() {
+            return Err(NetworkError::WrongWeightCount { expected: topology.weight_count(), got: weights.len() });
+        }
+
+        let (encoder, head) = weights.split_at(Network::weight_count(&topology.encoder));
+        Ok(Self {
+            topology: topology.clone(),
+            encoder: Network::try_from_weights(&topology.encoder, encoder.iter().copied())?,
+            head: Network::try_from_weights(&topology.head, head.iter().copied())?,
+        })
+    }
+
+    pub fn weights(&self) -> Vec<f32> {
+        let mut weights = self.encoder.weights();
+        weights.extend(self.head.weights());
+        weights
+    }
+
+    pub fn topology(&self) -> &SetTopology {
+        &self.topology
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.head.output_size()
+    }
+
+    pub fn scratch(&self) -> SetScratch {
+        SetScratch {
+            encoder: self.encoder.scratch(),
+            head: self.head.scratch(),
+            encoded: vec![0.0; self.encoder.output_size()],
+            head_input: vec![0.0; self.head.input_size()],
+        }
+    }
+
+    /// `input` is a whole number of entities followed by `context_size` values
+    pub fn propagate_into(&self, input: &[f32], scratch: &mut SetScratch, output: &mut [f32]) {
+        let SetTopology { entity_size, context_size, presence, pooling, .. } = self.topology;
+        assert!(input.len() >= context_size && (input.len() - context_size).is_multiple_of(entity_size), "input is not a list of entities followed by the context");
+
+        let (entities, context) = input.split_at(input.len() - context_size);
+        let pooled_size = scratch.encoded.len();
+        let (pooled, head_context) = scratch.head_input.split_at_mut(pooled_size);
+        let start = if pooling == Pooling::Max { f32::MIN } else { 0.0 };
+        pooled.iter_mut().for_each(|x| *x = start);
+
+        let mut count = 0;
+        for entity in entities.chunks_exact(entity_size) {
+            if presence.is_some_and(|idx| entity[idx] == 0.0) {
+                continue;
+            }
+            count += 1;
+            self.encoder.propagate_into(entity, &mut scratch.encoder, &mut scratch.encoded);
+            for (pool, &x) in pooled.iter_mut().zip(&scratch.encoded) {
+                *pool = match pooling {
+                    Pooling::Sum | Pooling::Mean => *pool + x,
+                    Pooling::Max => pool.max(x),
+                };
+            }
+        }
+
+        //an empty set pools to zeros whatever the pooling
+        if count == 0 {
+            pooled.iter_mut().for_each(|x| *x = 0.0);
+        } else if pooling == Pooling::Mean {
+            pooled.iter_mut().for_each(|x| *x /= count as f32);
+        }
+
+        head_context.copy_from_slice(context);
+        self.head.propagate_into(&scratch.head_input, &mut scratch.head, output);
+    }
+}
+
+impl SetScratch {
+    pub fn reset(&mut self) {
+        self.head.reset();
+    }
+}
@@ -0,0 +1,49 @@
+use crate::*;
+
+/// Everything a single `Network::propagate_traced` call computed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trace {
+    pub input: Vec<f32>,
+    /// One entry per layer after the input layer
+    pub layers: Vec<LayerTrace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerTrace {
+    pub activation: Activation,
+    pub kind: LayerKind,
+    /// Values the layer received, without the recurrent memory
+    pub input: Vec<f32>,
+    /// Weighted sums including bias and recurrent terms
+    pub pre: Vec<f32>,
+    /// `pre` after the activation, what the next layer sees
+    pub post: Vec<f32>,
+}
+
+impl Trace {
+    pub fn output(&self) -> &[f32] {
+        self.layers.last().map_or(&self.input, |layer| &layer.post)
+    }
+}
+
+impl Network {
+    /// Same as `propagate_into`, including the update of the recurrent memory in `scratch`,
+    /// but keeps every intermediate value. Allocates, meant for introspection rather than the hot loop.
+    pub fn propagate_traced(&self, input: &[f32], scratch: &mut Scratch) -> Trace {
+        assert_eq!(input.len(), self.input_size());
+        assert_eq!(scratch.memory.len(), self.layers.len(), "scratch belongs to a different network");
+
+        let mut layers: Vec<LayerTrace> = Vec::with_capacity(self.layers.len());
+        for (layer, memory) in self.layers.iter().zip(scratch.memory.iter_mut()) {
+            let layer_input = layers.last().map_or(input, |prev| &prev.post).to_vec();
+            let mut pre = vec![0.0; layer.output_size];
+            let mut post = vec![0.0; layer.output_size];
+
+            layer.preactivate(&layer_input, memory, &mut pre);
+            layer.propagate(&layer_input, memory, &mut post);
+            layers.push(LayerTrace { activation: layer.activation, kind: layer.kind, input: layer_input, pre, post });
+        }
+
+        Trace { input: input.to_vec(), layers }
+    }
+}
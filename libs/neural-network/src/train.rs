@@ -0,0 +1,157 @@
+use crate::*;
+use rand::seq::SliceRandom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Optimizer {
+    /// Plain gradient descent, momentum 0.0 disables the velocity term
+    Sgd { learning_rate: f32, momentum: f32 },
+    Adam { learning_rate: f32, beta1: f32, beta2: f32, epsilon: f32 },
+}
+
+impl Optimizer {
+    pub fn sgd(learning_rate: f32) -> Self {
+        Optimizer::Sgd { learning_rate, momentum: 0.0 }
+    }
+
+    /// Adam with the usual defaults from the paper
+    pub fn adam(learning_rate: f32) -> Self {
+        Optimizer::Adam { learning_rate, beta1: 0.9, beta2: 0.999, epsilon: 1e-8 }
+    }
+}
+
+/// Fits a network to (observation, action) pairs by minimizing the mean squared error.
+/// Only dense layers are supported, there is no backpropagation through time.
+/// The trainer keeps the optimizer state, so reuse it for every batch of the same network.
+#[derive(Debug, Clone)]
+pub struct Trainer {
+    optimizer: Optimizer,
+    steps: i32,
+    //per layer, same layout as Layer::matrix
+    gradients: Vec<Vec<f32>>,
+    first_moments: Vec<Vec<f32>>,
+    second_moments: Vec<Vec<f32>>,
+}
+
+impl Trainer {
+    pub fn new(network: &Network, optimizer: Optimizer) -> Result<Self, NetworkError> {
+        if let Some(idx) = network.layers.iter().position(|layer| layer.kind != LayerKind::Dense) {
+            return Err(NetworkError::NotTrainable(idx + 1));
+        }
+
+        let zeros: Vec<Vec<f32>> = network.layers.iter().map(|layer| vec![0.0; layer.matrix.len()]).collect();
+        Ok(Self {
+            optimizer,
+            steps: 0,
+            gradients: zeros.clone(),
+            first_moments: zeros.clone(),
+            second_moments: zeros,
+        })
+    }
+
+    /// One optimizer step on the mean gradient of `samples`, returns the loss before the step
+    pub fn train_batch(&mut self, network: &mut Network, samples: &[(Vec<f32>, Vec<f32>)]) -> Result<f32, NetworkError> {
+        self.step(network, samples.iter().enumerate())
+    }
+
+    /// Shuffled minibatch training, returns the mean loss of every epoch
+    pub fn fit(&mut self, rng: &mut dyn rand::RngCore, network: &mut Network, samples: &[(Vec<f32>, Vec<f32>)], epochs: usize, batch_size: usize) -> Result<Vec<f32>, NetworkError> {
+        assert!(batch_size > 0);
+        let mut order: Vec<usize> = (0..samples.len()).collect();
+        let mut losses = Vec::with_capacity(epochs);
+
+        for _ in 0..epochs {
+            order.shuffle(rng);
+            let mut epoch_loss = 0.0;
+            for batch in order.chunks(batch_size) {
+                epoch_loss += self.step(network, batch.iter().map(|&idx| (idx, &samples[idx])))? * batch.len() as f32;
+            }
+            losses.push(epoch_loss / samples.len().max(1) as f32);
+        }
+
+        Ok(losses)
+    }
+
+    //samples come with their index in the dataset for error reporting
+    fn step<'a>(&mut self, network: &mut Network, samples: impl Iterator<Item = (usize, &'a (Vec<f32>, Vec<f32>))>) -> Result<f32, NetworkError> {
+        assert_eq!(self.gradients.len(), network.layers.len(), "trainer belongs to a different network");
+
+        for gradient in &mut self.gradients {
+            gradient.iter_mut().for_each(|g| *g = 0.0);
+        }
+
+        let mut scratch = network.scratch();
+        let (mut loss, mut count) = (0.0, 0);
+        for (idx, (input, target)) in samples {
+            if input.len() != network.input_size() || target.len() != network.output_size() {
+                return Err(NetworkError::SampleShape(idx));
+            }
+            loss += self.backpropagate(network, input, target, &mut scratch);
+            count += 1;
+        }
+        if count == 0 {
+            return Ok(0.0);
+        }
+
+        let scale = 1.0 / count as f32;
+        self.steps += 1;
+        for (idx, layer) in network.layers.iter_mut().enumerate() {
+            self.apply(idx, &mut layer.matrix, scale);
+        }
+
+        Ok(loss * scale)
+    }
+
+    //adds the gradient of one sample to self.gradients and returns its loss
+    fn backpropagate(&mut self, network: &Network, input: &[f32], target: &[f32], scratch: &mut Scratch) -> f32 {
+        let trace = network.propagate_traced(input, scratch);
+        let output = trace.output();
+        let loss = output.iter().zip(target).map(|(y, t)| (y - t) * (y - t)).sum::<f32>() / output.len() as f32;
+
+        //dloss/dpost of the current layer, starting at the output
+        let mut delta: Vec<f32> = output.iter().zip(target).map(|(y, t)| 2.0 * (y - t) / output.len() as f32).collect();
+
+        for (idx, (layer, layer_trace)) in network.layers.iter().zip(&trace.layers).enumerate().rev() {
+            for ((d, &pre), &post) in delta.iter_mut().zip(&layer_trace.pre).zip(&layer_trace.post) {
+                *d *= layer.activation.derivative(pre, post);
+            }
+
+            let stride = layer.stride();
+            let mut input_delta = vec![0.0; layer.input_size];
+            for ((row, gradient), &d) in layer.matrix.chunks_exact(stride).zip(self.gradients[idx].chunks_exact_mut(stride)).zip(&delta) {
+                gradient[0] += d;
+                for (((g, &w), &x), input_d) in gradient[1..].iter_mut().zip(&row[1..]).zip(&layer_trace.input).zip(input_delta.iter_mut()) {
+                    *g += d * x;
+                    *input_d += d * w;
+                }
+            }
+            delta = input_delta;
+        }
+
+        loss
+    }
+
+    fn apply(&mut self, idx: usize, matrix: &mut [f32], scale: f32) {
+        let gradients = &self.gradients[idx];
+        let first = &mut self.first_moments[idx];
+        let second = &mut self.second_moments[idx];
+
+        match self.optimizer {
+            Optimizer::Sgd { learning_rate, momentum } => {
+                for ((w, &g), v) in matrix.iter_mut().zip(gradients).zip(first.iter_mut()) {
+                    *v = momentum * *v - learning_rate * g * scale;
+                    *w += *v;
+                }
+            }
+            Optimizer::Adam { learning_rate, beta1, beta2, epsilon } => {
+                let correction1 = 1.0 - beta1.powi(self.steps);
+                let correction2 = 1.0 - beta2.powi(self.steps);
+                for (((w, &g), m), v) in matrix.iter_mut().zip(gradients).zip(first.iter_mut()).zip(second.iter_mut()) {
+                    let g = g * scale;
+                    *m = beta1 * *m + (1.0 - beta1) * g;
+                    *v = beta2 * *v + (1.0 - beta2) * g * g;
+                    *w -= learning_rate * (*m / correction1) / ((*v / correction2).sqrt() + epsilon);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::*;
+
+const GRID: usize = 8; //cells per side of the coverage grid, GRID * GRID bits have to fit the mask
+const CHECKPOINTS: usize = 4;
+
+//what an agent did during one generation, novelty search and MAP-Elites compare agents by it
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Behaviour {
+    //one bit per visited cell of the world
+    visited: u64,
+    //plants eaten or hervors killed by the end of every quarter of the generation
+    counts: [usize; CHECKPOINTS],
+    steps: usize,
+    total_speed: f32,
+    //radians turned either way
+    total_turn: f32,
+    heading: Option<f32>,
+}
+
+impl Behaviour {
+    pub(crate) fn record(&mut self, position: na::Point2<f32>, rotation: na::Rotation2<f32>, speed: f32, count: usize, age: usize, generation_length: usize) {
+        self.steps += 1;
+        self.total_speed += speed;
+        if let Some(heading) = self.heading {
+            self.total_turn += na::wrap(rotation.angle() - heading, -PI, PI).abs();
+        }
+        self.heading = Some(rotation.angle());
+
+        let cell = |coord: f32| ((coord * GRID as f32) as isize).clamp(0, GRID as isize - 1) as usize;
+        self.visited |= 1 << (cell(position.y) * GRID + cell(position.x));
+
+        //later steps of the same quarter overwrite it, so it ends up with the count at its end
+        let checkpoint = (age * CHECKPOINTS / generation_length.max(1)).min(CHECKPOINTS - 1);
+        self.counts[checkpoint] = count;
+        //quarters an agent didn't live through keep the count it died with
+        for later in &mut self.counts[checkpoint + 1..] {
+            *later = count;
+        }
+    }
+
+    //dead hervors stop recording, so for them it's the steps they survived
+    pub(crate) fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub(crate) fn average_speed(&self) -> f32 {
+        self.total_speed / self.steps.max(1) as f32
+    }
+
+    //radians per step
+    pub(crate) fn turn_rate(&self) -> f32 {
+        self.total_turn / self.steps.saturating_sub(1).max(1) as f32
+    }
+
+    pub(crate) fn coverage(&self) -> f32 {
+        self.visited.count_ones() as f32 / (GRID * GRID) as f32
+    }
+
+    //final position, share of the grid visited and the count over time divided by `most`, all about 0..1
+    pub(crate) fn descriptor(&self, position: na::Point2<f32>, most: usize) -> Vec<f32> {
+        let mut descriptor = vec![position.x, position.y, self.coverage()];
+        descriptor.extend(self.counts.iter().map(|&count| count as f32 / most.max(1) as f32));
+        descriptor
+    }
+
+    pub(crate) fn feature(&self, feature: Feature, position: na::Point2<f32>, most: usize) -> f32 {
+        match feature {
+            Feature::AverageSpeed => self.average_speed(),
+            Feature::TurnRate => self.turn_rate(),
+            Feature::Coverage => self.coverage(),
+            Feature::Count => self.counts[CHECKPOINTS - 1] as f32 / most.max(1) as f32,
+            Feature::FinalX => position.x,
+            Feature::FinalY => position.y,
+        }
+    }
+}
@@ -2,8 +2,200 @@ use crate::*;
 
 pub trait Brain {
     fn random(rng: &mut dyn RngCore, sim_conf: &SimulationConfig, in_conf: &IndividualConfig) -> Self where Self: Sized;
+    fn propagate(&mut self, inputs: &[f32]) -> &[f32];
+    //network and its per-agent memory, used by the batched step, None if the brain can't be batched
+    fn network(&mut self) -> Option<(&nn::Network, &mut nn::Scratch)>;
     fn as_chromosome(&self) -> Chromosome;
     fn from_chromosome(chromosome: Chromosome, sim_conf: &SimulationConfig, in_conf: &IndividualConfig) -> Self where Self: Sized;
     fn topology(sim_conf: &SimulationConfig, in_conf: &IndividualConfig) -> Vec<nn::LayerTopology> where Self: Sized;
     fn input_size(sim_conf: &SimulationConfig, in_conf: &IndividualConfig) -> usize;
 }
+
+//one line of save_data/brains
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) enum SavedBrain {
+    //self-describing, loads regardless of the current topology
+    Network(nn::Network),
+    //anything that isn't a layered network, decoded with the saved config
+    Genes(Vec<f32>),
+    //self-describing too, loads regardless of how many entities the eye sees
+    Set(nn::SetNetwork),
+}
+
+//everything a controller needs to build a brain: the layers of a flat brain,
+//and for set encoded brains the encoder and head that take their place
+pub(crate) struct BrainShape {
+    pub(crate) layers: Vec<nn::LayerTopology>,
+    pub(crate) set: Option<nn::SetTopology>,
+}
+
+impl BrainShape {
+    //set encoded brains pool entities of entity_size features and pass the context after them on to the
+    //usual hidden layers. edit the encoder here
+    pub(crate) fn new(layers: Vec<nn::LayerTopology>, set_encoder: bool, entity_size: usize, context_size: usize) -> Self {
+        let set = set_encoder.then(|| {
+            let encoder = vec![
+                nn::LayerTopology { neurons: entity_size, activation: nn::Activation::Identity, kind: nn::LayerKind::Dense },
+                nn::LayerTopology { neurons: 16, activation: nn::Activation::ReLU, kind: nn::LayerKind::Dense },
+            ];
+            let mut head = layers.clone();
+            head[0].neurons = encoder[encoder.len() - 1].neurons + context_size;
+
+            nn::SetTopology { entity_size, context_size, presence: Some(entity_size - 1), pooling: nn::Pooling::Sum, encoder, head }
+        });
+
+        Self { layers, set }
+    }
+
+    //what save_data/weights was trained with, every layer went through ReLU before layers had their own activations
+    pub(crate) fn legacy(&self) -> Self {
+        let layers = self.layers.iter().map(|&layer| nn::LayerTopology { activation: nn::Activation::ReLU, ..layer }).collect();
+        Self { layers, set: self.set.clone() }
+    }
+
+    //gene count of every neuron in a directly encoded chromosome
+    pub(crate) fn neuron_sizes(&self) -> Vec<usize> {
+        match &self.set {
+            Some(set) => set.neuron_sizes(),
+            None => nn::Network::neuron_sizes(&self.layers),
+        }
+    }
+}
+
+//what actually drives an agent, shared by hervor and chaser brains
+#[derive(Debug, Clone)]
+pub(crate) enum Controller {
+    Layered { nn: nn::Network, scratch: nn::Scratch },
+    Neat { genome: nn::neat::Genome, network: nn::neat::NeatNetwork, values: Vec<f32> },
+    Set { nn: nn::SetNetwork, scratch: nn::SetScratch },
+    Cppn { genome: nn::neat::Genome, nn: nn::Network, scratch: nn::Scratch },
+}
+
+impl Controller {
+    pub(crate) fn layered(nn: nn::Network) -> Self {
+        let scratch = nn.scratch();
+        Controller::Layered { nn, scratch }
+    }
+
+    pub(crate) fn set(nn: nn::SetNetwork) -> Self {
+        let scratch = nn.scratch();
+        Controller::Set { nn, scratch }
+    }
+
+    pub(crate) fn cppn(genome: nn::neat::Genome, topology: &[nn::LayerTopology]) -> Result<Self, nn::NetworkError> {
+        let nn = nn::Network::from_cppn(&genome.network(), topology)?;
+        let scratch = nn.scratch();
+        Ok(Controller::Cppn { genome, nn, scratch })
+    }
+
+    pub(crate) fn neat(genome: nn::neat::Genome) -> Self {
+        let network = genome.network();
+        let values = network.values();
+        Controller::Neat { genome, network, values }
+    }
+
+    //genes of the encoding the controller was built from, see Encoding
+    pub(crate) fn genes(&self) -> Vec<f32> {
+        match self {
+            Controller::Layered { nn, .. } => nn.weights(),
+            Controller::Neat { genome, .. } | Controller::Cppn { genome, .. } => genome.to_genes(),
+            Controller::Set { nn, .. } => nn.weights(),
+        }
+    }
+
+    //None if the brain takes any number of entities
+    pub(crate) fn input_size(&self) -> Option<usize> {
+        match self {
+            Controller::Layered { nn, .. } | Controller::Cppn { nn, .. } => Some(nn.input_size()),
+            Controller::Neat { network, .. } => Some(network.input_size()),
+            Controller::Set { .. } => None,
+        }
+    }
+
+    pub(crate) fn output_size(&self) -> usize {
+        match self {
+            Controller::Layered { nn, .. } | Controller::Cppn { nn, .. } => nn.output_size(),
+            Controller::Neat { network, .. } => network.output_size(),
+            Controller::Set { nn, .. } => nn.output_size(),
+        }
+    }
+
+    pub(crate) fn propagate_into(&mut self, inputs: &[f32], output: &mut [f32]) {
+        match self {
+            Controller::Layered { nn, scratch } | Controller::Cppn { nn, scratch, .. } => nn.propagate_into(inputs, scratch, output),
+            Controller::Neat { network, values, .. } => network.propagate_into(inputs, values, output),
+            Controller::Set { nn, scratch } => nn.propagate_into(inputs, scratch, output),
+        }
+    }
+
+    pub(crate) fn network(&mut self) -> Option<(&nn::Network, &mut nn::Scratch)> {
+        match self {
+            Controller::Layered { nn, scratch } | Controller::Cppn { nn, scratch, .. } => Some((nn, scratch)),
+            Controller::Neat { .. } | Controller::Set { .. } => None,
+        }
+    }
+
+    pub(crate) fn random(rng: &mut dyn RngCore, in_conf: &IndividualConfig, shape: &BrainShape) -> Self {
+        let topology = &shape.layers;
+        match (in_conf.encoding, &shape.set) {
+            (Encoding::Direct, Some(set)) => {
+                Self::set(nn::SetNetwork::random(rng, set, in_conf.init, in_conf.zero_bias).unwrap_or_else(|err| panic!("{err}")))
+            }
+            (Encoding::Direct, None) => {
+                let nn = nn::Network::new(topology)
+                    .with_init(in_conf.init)
+                    .with_zero_bias(in_conf.zero_bias)
+                    .build(rng)
+                    .unwrap_or_else(|err| panic!("{err}"));
+                Self::layered(nn)
+            }
+            (Encoding::Cppn, _) => Self::cppn(nn::cppn::minimal(rng), topology).unwrap_or_else(|err| panic!("{err}")),
+            (Encoding::Neat, _) => {
+                let outputs = topology[topology.len() - 1].neurons;
+                Self::neat(nn::neat::Genome::minimal(rng, topology[0].neurons, outputs, nn::Activation::Tanh, nn::Activation::Tanh))
+            }
+        }
+    }
+
+    pub(crate) fn from_chromosome(chromosome: Chromosome, in_conf: &IndividualConfig, shape: &BrainShape) -> Self {
+        Self::try_from_chromosome(chromosome, in_conf, shape).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub(crate) fn try_from_chromosome(chromosome: Chromosome, in_conf: &IndividualConfig, shape: &BrainShape) -> Result<Self, nn::NetworkError> {
+        match (in_conf.encoding, &shape.set) {
+            (Encoding::Direct, Some(set)) => nn::SetNetwork::try_from_weights(set, chromosome).map(Self::set),
+            (Encoding::Direct, None) => nn::Network::try_from_weights(&shape.layers, chromosome).map(Self::layered),
+            (Encoding::Neat, _) => nn::neat::Genome::try_from_genes(&chromosome.into_iter().collect::<Vec<f32>>()).map(Self::neat),
+            (Encoding::Cppn, _) => Self::cppn(nn::neat::Genome::try_from_genes(&chromosome.into_iter().collect::<Vec<f32>>())?, &shape.layers),
+        }
+    }
+
+    pub(crate) fn to_saved(&self) -> SavedBrain {
+        match self {
+            Controller::Layered { nn, .. } => SavedBrain::Network(nn.clone()),
+            Controller::Neat { genome, .. } | Controller::Cppn { genome, .. } => SavedBrain::Genes(genome.to_genes()),
+            Controller::Set { nn, .. } => SavedBrain::Set(nn.clone()),
+        }
+    }
+
+    pub(crate) fn try_from_saved(saved: SavedBrain, in_conf: &IndividualConfig, shape: &BrainShape) -> Result<Self, String> {
+        let controller = match saved {
+            SavedBrain::Network(nn) => Self::layered(nn),
+            SavedBrain::Genes(genes) => Self::try_from_chromosome(Chromosome::new(genes), in_conf, shape).map_err(|err| err.to_string())?,
+            SavedBrain::Set(nn) => {
+                let fits = shape.set.as_ref().is_some_and(|set| set.entity_size == nn.topology().entity_size && set.context_size == nn.topology().context_size);
+                if !fits {
+                    return Err(String::from("saved set encoded brain does not fit the configured eye"));
+                }
+                Self::set(nn)
+            }
+        };
+        if let Some(input_size) = controller.input_size() {
+            if input_size != shape.layers[0].neurons {
+                return Err(format!("saved brain takes {input_size} inputs, the configured eye gives {}", shape.layers[0].neurons));
+            }
+        }
+
+        Ok(controller)
+    }
+}
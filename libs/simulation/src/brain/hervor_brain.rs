@@ -2,24 +2,43 @@ use crate::*;
 
 #[derive(Debug, Clone)]
 pub struct HervorBrain {
-    pub(crate) nn: nn::Network
+    pub(crate) controller: Controller,
+    output: Vec<f32>,
+}
+
+impl HervorBrain {
+    pub(crate) fn from_controller(controller: Controller) -> Self {
+        let output = vec![0.0; controller.output_size()];
+        Self { controller, output }
+    }
+
+    //POSITIONAL vision is 4 values per chaser followed by closest plant and the time, the set encoder pools the chasers
+    pub(crate) fn shape(sim_conf: &SimulationConfig, hervor_conf: &IndividualConfig) -> BrainShape {
+        let set_encoder = hervor_conf.set_encoder && hervor_conf.training_model == Model::POSITIONAL;
+        BrainShape::new(Self::topology(sim_conf, hervor_conf), set_encoder, 4, 3 + 1)
+    }
 }
 
 impl Brain for HervorBrain{
     fn random(rng: &mut dyn RngCore, sim_conf: &SimulationConfig, hervor_conf: &IndividualConfig) -> Self {
-        Self {
-            nn: nn::Network::random(rng, &Self::topology(sim_conf, hervor_conf))
-        }
+        Self::from_controller(Controller::random(rng, hervor_conf, &Self::shape(sim_conf, hervor_conf)))
+    }
+
+    fn propagate(&mut self, inputs: &[f32]) -> &[f32] {
+        self.controller.propagate_into(inputs, &mut self.output);
+        &self.output
+    }
+
+    fn network(&mut self) -> Option<(&nn::Network, &mut nn::Scratch)> {
+        self.controller.network()
     }
 
     fn as_chromosome(&self) -> Chromosome {
-        Chromosome::new(self.nn.weights())
+        Chromosome::new(self.controller.genes())
     }
 
     fn from_chromosome(chromosome: Chromosome, sim_conf: &SimulationConfig, hervor_conf: &IndividualConfig) -> Self {
-        Self {
-            nn: nn::Network::from_weights(&Self::topology(sim_conf, hervor_conf), chromosome)
-        }
+        Self::from_controller(Controller::from_chromosome(chromosome, hervor_conf, &Self::shape(sim_conf, hervor_conf)))
     }
 
     fn input_size(sim_conf: &SimulationConfig, hervor_conf: &IndividualConfig) -> usize {
@@ -28,7 +47,7 @@ impl Brain for HervorBrain{
                 sim_conf.nchasers * 4 + 3 + 1
             }
             Model::CELLULAR => {
-                hervor_conf.eye_cells + 1
+                hervor_conf.eye_cells * 2 + 1 //plant cells, chaser cells, time
             }
             Model::CLOSEST => {
                 4 + 3 + 1
@@ -36,21 +55,30 @@ impl Brain for HervorBrain{
         }
     }
 
-    //edit topology here
+    //edit topology here, NEAT brains only use the input and output sizes
     fn topology(sim_conf: &SimulationConfig, hervor_conf: &IndividualConfig) -> Vec<nn::LayerTopology> {
         let in_size = Self::input_size(sim_conf, hervor_conf);
 
+        let memory_kind = if hervor_conf.recurrent { nn::LayerKind::Recurrent } else { nn::LayerKind::Dense };
+        let sensory_kind = if hervor_conf.plastic { nn::LayerKind::Plastic } else { nn::LayerKind::Dense };
+
         vec![
             nn::LayerTopology {
                 neurons: in_size,
+                activation: nn::Activation::Identity,
+                kind: nn::LayerKind::Dense,
             },
             nn::LayerTopology {
                 neurons: 32,
+                activation: nn::Activation::ReLU,
+                kind: sensory_kind,
             },
             nn::LayerTopology {
                 neurons: 32,
+                activation: nn::Activation::ReLU,
+                kind: memory_kind,
             },
-            nn::LayerTopology { neurons: 2 },
+            nn::LayerTopology { neurons: 2, activation: nn::Activation::Tanh, kind: nn::LayerKind::Dense }, //signed speed and rotation deltas
         ]
     }
 }
\ No newline at end of file
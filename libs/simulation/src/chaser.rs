@@ -9,7 +9,13 @@ pub struct Chaser {
     pub(crate) speed: f32,
     pub(crate) eye: Box<dyn Eye>,
     pub(crate) killed: usize,
-    pub(crate) brain: ChaserBrain
+    pub(crate) brain: ChaserBrain,
+    //fitness of an elite that skips re-evaluation, it replaces whatever it scores this generation
+    pub(crate) kept_fitness: Option<f32>,
+    //self-adaptive mutation step sizes, handed back to the GA with the chromosome
+    pub(crate) step_sizes: Vec<f32>,
+    //where it went and how it fed this generation, for novelty search
+    pub(crate) behaviour: Behaviour,
 }
 impl Chaser {
     pub fn new(eye: Box<dyn Eye>, brain: ChaserBrain, rng: &mut dyn RngCore) -> Self {
@@ -19,6 +25,9 @@ impl Chaser {
             speed: 0.002,
             eye,
             brain,
+            kept_fitness: None,
+            step_sizes: Vec::new(),
+            behaviour: Behaviour::default(),
             killed: 0, 
         }
     }
@@ -31,7 +40,7 @@ impl Chaser {
                 Self::new(Box::new(peye), brain, rng)
             }
             Model::CELLULAR => {
-                let ceye = CellularEye::new(chaser_conf.fov_range, chaser_conf.fov_angle);
+                let ceye = CellularEye::with_cells(chaser_conf.fov_range, chaser_conf.fov_angle, chaser_conf.eye_cells);
                 let brain = ChaserBrain::random(rng, sim_conf, chaser_conf);
                 Self::new(Box::new(ceye), brain, rng)
             }
@@ -59,7 +68,7 @@ impl Chaser {
                 Self::new(Box::new(peye), brain, rng)
             }
             Model::CELLULAR => {
-                let ceye = CellularEye::new(chaser_conf.fov_range, chaser_conf.fov_angle);
+                let ceye = CellularEye::with_cells(chaser_conf.fov_range, chaser_conf.fov_angle, chaser_conf.eye_cells);
                 let brain = Brain::from_chromosome(chromosome, sim_conf, chaser_conf);
                 Self::new(Box::new(ceye), brain, rng)
             }
@@ -72,7 +81,9 @@ impl Chaser {
     }
 
     pub(crate) fn as_chromosome(&self) -> Chromosome {
-        self.brain.as_chromosome()
+        let mut chromosome = self.brain.as_chromosome();
+        chromosome.set_step_sizes(self.step_sizes.clone());
+        chromosome
     }
 }
 
@@ -87,7 +98,7 @@ impl Chaser{
                     Box::new(PositionalEye::new(chaser_conf.fov_range, chaser_conf.fov_angle))
                 },
                 Model::CELLULAR => {
-                    Box::new(CellularEye::new(chaser_conf.fov_range, chaser_conf.fov_angle))
+                    Box::new(CellularEye::with_cells(chaser_conf.fov_range, chaser_conf.fov_angle, chaser_conf.eye_cells))
                 },
                 Model::CLOSEST => {
                     Box::new(ClosestEye::new(chaser_conf.fov_range, chaser_conf.fov_angle))
@@ -95,6 +106,9 @@ impl Chaser{
             },
             killed: self.killed,
             brain: self.brain.clone(),
+            kept_fitness: self.kept_fitness,
+            step_sizes: self.step_sizes.clone(),
+            behaviour: self.behaviour.clone(),
         }
     }
 }
\ No newline at end of file
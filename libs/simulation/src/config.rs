@@ -1,10 +1,78 @@
 use serde;
+pub use lib_neural_network::Init;
+use lib_genetic_algorithm::EvolutionPolicy;
 
 #[derive(serde::Serialize, serde::Deserialize, PartialEq, Clone)]
 pub enum Model{
     POSITIONAL, CELLULAR, CLOSEST
 }
 
+//how a chromosome is turned into a brain
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Clone, Copy, Debug, Default)]
+pub enum Encoding{
+    //genes are the weights of the fixed topology
+    #[default]
+    Direct,
+    //genes are a flattened NEAT genome, the topology evolves
+    Neat,
+    //genes are a flattened NEAT genome of a CPPN that paints the weights of the fixed topology,
+    //the genome stays small however wide the eye and the hidden layers are
+    Cppn,
+}
+
+//what an agent did during a generation, a MAP-Elites axis
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum Feature{
+    //distance per step
+    AverageSpeed,
+    //radians turned either way per step
+    TurnRate,
+    //share of the world's 8x8 cells it went through
+    Coverage,
+    //plants eaten out of nplants for hervors, hervors killed out of nhervors for chasers
+    Count,
+    FinalX,
+    FinalY,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct BehaviourAxis{
+    pub feature: Feature,
+    pub min: f32,
+    pub max: f32,
+    pub bins: usize,
+}
+
+impl BehaviourAxis{
+    pub fn dimension(&self) -> lib_genetic_algorithm::Dimension {
+        lib_genetic_algorithm::Dimension { min: self.min, max: self.max, bins: self.bins }
+    }
+}
+
+//what evolves a species from one generation to the next
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Clone, Debug, Default)]
+pub enum Strategy{
+    //the genetic algorithm set up by SimulationConfig
+    #[default]
+    Genetic,
+    //CMA-ES starting at step size sigma, its state is saved with the simulation. Direct encoding only,
+    //NEAT and CPPN genomes keep the GA. Elitism doesn't apply
+    CmaEs { sigma: f32 },
+    //OpenAI-ES, every agent is the mean plus or minus gaussian noise of scale sigma and the ranked fitness
+    //of those pairs steps the mean with Adam. With one agent per world the pairs sit in neighbouring worlds,
+    //so the more worlds the better the gradient. Direct encoding only, state and elitism like CMA-ES
+    OpenAiEs { sigma: f32, learning_rate: f32 },
+    //MAP-Elites over a grid with one dimension per axis, every cell keeps the fittest agent that behaved like that
+    //and new agents are bred from random cells with the GA's crossover and mutation. Works with every encoding,
+    //the grid isn't saved with the simulation but exported as save_data/{hervor,chaser}_map_elites.csv
+    MapElites { axes: Vec<BehaviourAxis> },
+    //NSGA-II over the objectives of the agents, hervors trade off plants eaten against steps survived and chasers
+    //only have their fitness. The survivors of every generation compete with the next, parents come from crowded
+    //tournaments among them and the first front goes to the training log. Works with every encoding like MAP-Elites,
+    //the survivors aren't saved and novelty doesn't change the objectives
+    Nsga2,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct IndividualConfig {
     pub speed_min: f32,
@@ -14,7 +82,30 @@ pub struct IndividualConfig {
     pub fov_range: f32,
     pub fov_angle: f32,
     pub eye_cells: usize,
-    pub training_model: Model
+    pub training_model: Model,
+    /// Makes the last hidden layer recurrent, its memory is wiped with every new generation
+    #[serde(default)]
+    pub recurrent: bool,
+    /// Makes the first hidden layer Hebbian, it keeps learning during a generation and starts over from the evolved weights with the next
+    #[serde(default)]
+    pub plastic: bool,
+    /// POSITIONAL only: the other species goes through a permutation-invariant set encoder,
+    /// the brain no longer depends on their order or number. Direct encoding only, NEAT and CPPN ignore it
+    #[serde(default)]
+    pub set_encoder: bool,
+    #[serde(default)]
+    pub encoding: Encoding,
+    /// How layered brains draw their weights in generation zero
+    #[serde(default)]
+    pub init: Init,
+    #[serde(default)]
+    pub zero_bias: bool,
+    #[serde(default)]
+    pub strategy: Strategy,
+    /// Blends the fitness with how novel the agent's behaviour is, the archive isn't saved with the simulation.
+    /// Elites are always re-evaluated since novelty only means something within a generation
+    #[serde(default)]
+    pub novelty: Option<lib_genetic_algorithm::NoveltySearch>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -24,9 +115,39 @@ pub struct SimulationConfig {
     pub nchasers: usize,
     pub nplants: usize,
     pub nworlds: usize,
-    pub safe_evolve: bool,
+    /// Elitism and the parent pool, configs saved before it have a `safe_evolve` bool in its place
+    #[serde(default, alias = "safe_evolve", deserialize_with = "evolution_or_safe_evolve")]
+    pub evolution: EvolutionPolicy,
     pub respawn_plants: bool,
     pub mutation_probability: f32,
     pub mutation_magnitude: f32,
     pub parallelized: bool,
-}
\ No newline at end of file
+    /// How parents are picked, shared by both species
+    #[serde(default)]
+    pub selection: lib_genetic_algorithm::Selection,
+    /// How two parents are mixed, shared by both species. Direct encoding only, NEAT and CPPN genomes align innovations instead
+    #[serde(default)]
+    pub crossover: lib_genetic_algorithm::Crossover,
+    /// How `mutation_magnitude` is applied, Direct encoding only like the crossover.
+    /// Self-adaptive step sizes live with the agents and aren't saved, loaded simulations start over from the magnitude
+    #[serde(default)]
+    pub mutation: lib_genetic_algorithm::Mutation,
+    /// Changes `mutation_probability` and `mutation_magnitude` over the generations, the effective rates end up in the statistics
+    #[serde(default)]
+    pub mutation_schedule: lib_genetic_algorithm::MutationSchedule,
+}
+
+fn evolution_or_safe_evolve<'de, D>(deserializer: D) -> Result<EvolutionPolicy, D::Error> where D: serde::Deserializer<'de> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Either {
+        Policy(EvolutionPolicy),
+        SafeEvolve(bool),
+    }
+
+    Ok(match serde::Deserialize::deserialize(deserializer)? {
+        Either::Policy(policy) => policy,
+        Either::SafeEvolve(true) => EvolutionPolicy::half_elitist(),
+        Either::SafeEvolve(false) => EvolutionPolicy::generational(),
+    })
+}
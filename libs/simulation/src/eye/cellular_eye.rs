@@ -9,6 +9,15 @@ pub struct CellularEye{
     cells: usize,
 }
 
+impl CellularEye{
+    //eye resolution comes from IndividualConfig::eye_cells
+    pub fn with_cells(fov_range: f32, fov_angle: f32, cells: usize) -> Self {
+        assert!(cells > 0);
+
+        Self { cells, ..Self::new(fov_range, fov_angle) }
+    }
+}
+
 impl Eye for CellularEye{
     fn new(fov_range: f32, fov_angle: f32) -> Self where Self:Sized {
         assert!(fov_angle > 0.0);
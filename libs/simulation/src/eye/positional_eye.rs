@@ -15,37 +15,37 @@ impl Eye for PositionalEye{
     }
 
     fn process_vision_see_chasers(&self, position: &na::Point2<f32>, rotation: &na::Rotation2<f32>, chasers: &[Chaser]) -> Vec<f32> {
-        let mut cells = vec![0.0; chasers.len() * 4]; //(x, y, can see)
+        let mut cells = vec![0.0; chasers.len() * 4]; //(x, y, relative angle, can see) per entity
 
         for i in 0..chasers.len() {
             let chaser = &chasers[i];
             let vec = chaser.position - position;
             let dist = vec.norm();
             if dist > self.fov_range { //out of range
-                cells[i] = 0.0;
-                cells[i + 1] = 0.0;
-                cells[i + 2] = 0.0;
-                cells[i + 3] = 0.0;
+                cells[i * 4] = 0.0;
+                cells[i * 4 + 1] = 0.0;
+                cells[i * 4 + 2] = 0.0;
+                cells[i * 4 + 3] = 0.0;
                 continue;
             }
             let angle = na::Rotation2::rotation_between(&na::Vector2::x(), &vec).angle();
             let angle = angle - rotation.angle();
             let angle = na::wrap(angle, -PI, PI);
             if angle < -self.fov_angle / 2.0 || angle > self.fov_angle / 2.0 { //out of angle
-                cells[i] = 0.0;
-                cells[i + 1] = 0.0;
-                cells[i + 2] = 0.0;
-                cells[i + 3] = 0.0;
+                cells[i * 4] = 0.0;
+                cells[i * 4 + 1] = 0.0;
+                cells[i * 4 + 2] = 0.0;
+                cells[i * 4 + 3] = 0.0;
                 continue;
             }
             
             let relx = chaser.position.x - position.x;
             let rely = chaser.position.y - position.y;
             let relangle = chaser.rotation.angle() - rotation.angle();
-            cells[i] = relx;
-            cells[i + 1] = rely;
-            cells[i + 2] = relangle;
-            cells[i + 3] = 1.0;
+            cells[i * 4] = relx;
+            cells[i * 4 + 1] = rely;
+            cells[i * 4 + 2] = relangle;
+            cells[i * 4 + 3] = 1.0;
         }
 
         return cells;
@@ -89,37 +89,37 @@ impl Eye for PositionalEye{
     }
 
     fn process_vision_see_hervors(&self, position: &na::Point2<f32>, rotation: &na::Rotation2<f32>, hervors: &[Hervor]) -> Vec<f32> {
-        let mut cells = vec![0.0; hervors.len() * 4]; //(x, y, can see)
+        let mut cells = vec![0.0; hervors.len() * 4]; //(x, y, relative angle, can see) per entity
 
         for i in 0..hervors.len() {
             let hervor = &hervors[i];
             let vec = hervor.position - position;
             let dist = vec.norm();
             if dist > self.fov_range { //out of range
-                cells[i] = 0.0;
-                cells[i + 1] = 0.0;
-                cells[i + 2] = 0.0;
-                cells[i + 3] = 0.0;
+                cells[i * 4] = 0.0;
+                cells[i * 4 + 1] = 0.0;
+                cells[i * 4 + 2] = 0.0;
+                cells[i * 4 + 3] = 0.0;
                 continue;
             }
             let angle = na::Rotation2::rotation_between(&na::Vector2::x(), &vec).angle();
             let angle = angle - rotation.angle();
             let angle = na::wrap(angle, -PI, PI);
             if angle < -self.fov_angle / 2.0 || angle > self.fov_angle / 2.0 { //out of angle
-                cells[i] = 0.0;
-                cells[i + 1] = 0.0;
-                cells[i + 2] = 0.0;
-                cells[i + 3] = 0.0;
+                cells[i * 4] = 0.0;
+                cells[i * 4 + 1] = 0.0;
+                cells[i * 4 + 2] = 0.0;
+                cells[i * 4 + 3] = 0.0;
                 continue;
             }
             
             let relx = hervor.position.x - position.x;
             let rely = hervor.position.y - position.y;
             let relangle = hervor.rotation.angle() - rotation.angle();
-            cells[i] = relx;
-            cells[i + 1] = rely;
-            cells[i + 2] = relangle;
-            cells[i + 3] = 1.0;
+            cells[i * 4] = relx;
+            cells[i * 4 + 1] = rely;
+            cells[i * 4 + 2] = relangle;
+            cells[i * 4 + 3] = 1.0;
         }
 
         return cells;
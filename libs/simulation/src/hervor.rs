@@ -10,7 +10,13 @@ pub struct Hervor{
     pub(crate) eye: Box<dyn Eye>,
     pub(crate) satiation: usize,
     pub(crate) dead: bool,
-    pub(crate) brain: HervorBrain
+    pub(crate) brain: HervorBrain,
+    //fitness of an elite that skips re-evaluation, it replaces whatever it scores this generation
+    pub(crate) kept_fitness: Option<f32>,
+    //self-adaptive mutation step sizes, handed back to the GA with the chromosome
+    pub(crate) step_sizes: Vec<f32>,
+    //where it went and how it fed this generation, for novelty search
+    pub(crate) behaviour: Behaviour,
 }
 impl Hervor {
     pub fn new(eye: Box<dyn Eye>, brain: HervorBrain, rng: &mut dyn RngCore) -> Self {
@@ -20,6 +26,9 @@ impl Hervor {
             speed: 0.002,
             eye,
             brain,
+            kept_fitness: None,
+            step_sizes: Vec::new(),
+            behaviour: Behaviour::default(),
             satiation: 0,
             dead: false
         }
@@ -37,7 +46,7 @@ impl Hervor {
                 Self::new(Box::new(peye), brain, rng)
             }
             Model::CELLULAR => {
-                let ceye = CellularEye::new(hervor_conf.fov_range, hervor_conf.fov_angle);
+                let ceye = CellularEye::with_cells(hervor_conf.fov_range, hervor_conf.fov_angle, hervor_conf.eye_cells);
                 let brain = HervorBrain::random(rng, sim_conf, hervor_conf);
                 Self::new(Box::new(ceye), brain, rng)
             }
@@ -65,7 +74,7 @@ impl Hervor {
                 Self::new(Box::new(peye), brain, rng)
             }
             Model::CELLULAR => {
-                let ceye = CellularEye::new(hervor_conf.fov_range, hervor_conf.fov_angle);
+                let ceye = CellularEye::with_cells(hervor_conf.fov_range, hervor_conf.fov_angle, hervor_conf.eye_cells);
                 let brain = Brain::from_chromosome(chromosome, sim_conf, hervor_conf);
                 Self::new(Box::new(ceye), brain, rng)
             }
@@ -78,7 +87,9 @@ impl Hervor {
     }
 
     pub(crate) fn as_chromosome(&self) -> Chromosome {
-        self.brain.as_chromosome()
+        let mut chromosome = self.brain.as_chromosome();
+        chromosome.set_step_sizes(self.step_sizes.clone());
+        chromosome
     }
 }
 
@@ -93,7 +104,7 @@ impl Hervor{
                     Box::new(PositionalEye::new(hervor_conf.fov_range, hervor_conf.fov_angle))
                 },
                 Model::CELLULAR => {
-                    Box::new(CellularEye::new(hervor_conf.fov_range, hervor_conf.fov_angle))
+                    Box::new(CellularEye::with_cells(hervor_conf.fov_range, hervor_conf.fov_angle, hervor_conf.eye_cells))
                 },
                 Model::CLOSEST => {
                     Box::new(ClosestEye::new(hervor_conf.fov_range, hervor_conf.fov_angle))
@@ -102,6 +113,9 @@ impl Hervor{
             dead: self.dead,
             satiation: self.satiation,
             brain: self.brain.clone(),
+            kept_fitness: self.kept_fitness,
+            step_sizes: self.step_sizes.clone(),
+            behaviour: self.behaviour.clone(),
         }
     }
 }
\ No newline at end of file
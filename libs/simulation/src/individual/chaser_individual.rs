@@ -4,6 +4,8 @@ use crate::*;
 pub struct ChaserIndividual{
     fitness: f32,
     chromosome: Chromosome,
+    //elites that aren't re-evaluated carry their fitness into the next generation
+    keep_fitness: bool,
 }
 
 impl Individual for ChaserIndividual {
@@ -11,6 +13,7 @@ impl Individual for ChaserIndividual {
         Self {
             fitness: 0.0,
             chromosome,
+            keep_fitness: false,
         }
     }
 
@@ -18,6 +21,7 @@ impl Individual for ChaserIndividual {
         Self {
             fitness,
             chromosome,
+            keep_fitness: true,
         }
     }
     
@@ -37,12 +41,22 @@ impl ChaserIndividual{
 
     pub(crate) fn from_chaser(chaser: &Chaser, kill_count: usize) -> Self {
         Self{
-            fitness: ChaserIndividual::calc_fitness(kill_count),
+            fitness: chaser.kept_fitness.unwrap_or_else(|| ChaserIndividual::calc_fitness(kill_count)),
             chromosome: chaser.as_chromosome(),
+            keep_fitness: false,
         }
     }
 
-    pub(crate) fn into_chaser(self, sim_conf: &SimulationConfig, chaser_conf: &IndividualConfig, rng: &mut dyn RngCore) -> Chaser {
-        Chaser::from_chromosome(sim_conf, chaser_conf, self.chromosome, rng)
+    //the same chromosome scored differently, e.g. blended with novelty
+    pub(crate) fn with_fitness(self, fitness: f32) -> Self {
+        Self { fitness, ..self }
+    }
+
+    pub(crate) fn into_chaser(mut self, sim_conf: &SimulationConfig, chaser_conf: &IndividualConfig, rng: &mut dyn RngCore) -> Chaser {
+        let step_sizes = self.chromosome.take_step_sizes();
+        let mut chaser = Chaser::from_chromosome(sim_conf, chaser_conf, self.chromosome, rng);
+        chaser.kept_fitness = self.keep_fitness.then_some(self.fitness);
+        chaser.step_sizes = step_sizes;
+        chaser
     }
 }
\ No newline at end of file
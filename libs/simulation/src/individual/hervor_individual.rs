@@ -4,6 +4,10 @@ use crate::*;
 pub struct HervorIndividual{
     fitness: f32,
     chromosome: Chromosome,
+    //elites that aren't re-evaluated carry their fitness into the next generation
+    keep_fitness: bool,
+    //plants eaten and steps survived, empty for children that haven't lived yet
+    objectives: Vec<f32>,
 }
 
 impl Individual for HervorIndividual {
@@ -11,6 +15,8 @@ impl Individual for HervorIndividual {
         Self {
             fitness: 0.0,
             chromosome,
+            keep_fitness: false,
+            objectives: Vec::new(),
         }
     }
     
@@ -18,6 +24,8 @@ impl Individual for HervorIndividual {
         Self {
             fitness,
             chromosome,
+            keep_fitness: true,
+            objectives: Vec::new(),
         }
     }
 
@@ -28,6 +36,14 @@ impl Individual for HervorIndividual {
     fn fitness(&self) -> f32 {
         self.fitness
     }
+
+    fn objectives(&self) -> Vec<f32> {
+        if self.objectives.is_empty() {
+            vec![self.fitness]
+        } else {
+            self.objectives.clone()
+        }
+    }
 }
 
 impl HervorIndividual{
@@ -37,12 +53,23 @@ impl HervorIndividual{
 
     pub(crate) fn from_hervor(hervor: &Hervor, nplants: usize) -> Self {
         Self{
-            fitness: HervorIndividual::calc_fitness(hervor, nplants),
+            fitness: hervor.kept_fitness.unwrap_or_else(|| HervorIndividual::calc_fitness(hervor, nplants)),
             chromosome: hervor.as_chromosome(),
+            keep_fitness: false,
+            objectives: vec![hervor.satiation as f32, hervor.behaviour.steps() as f32],
         }
     }
 
-    pub(crate) fn into_hervor(self, sim_conf: &SimulationConfig, hervor_conf: &IndividualConfig, rng: &mut dyn RngCore) -> Hervor {
-        Hervor::from_chromosome(sim_conf, hervor_conf, self.chromosome, rng)
+    //the same chromosome scored differently, e.g. blended with novelty
+    pub(crate) fn with_fitness(self, fitness: f32) -> Self {
+        Self { fitness, ..self }
+    }
+
+    pub(crate) fn into_hervor(mut self, sim_conf: &SimulationConfig, hervor_conf: &IndividualConfig, rng: &mut dyn RngCore) -> Hervor {
+        let step_sizes = self.chromosome.take_step_sizes();
+        let mut hervor = Hervor::from_chromosome(sim_conf, hervor_conf, self.chromosome, rng);
+        hervor.kept_fitness = self.keep_fitness.then_some(self.fitness);
+        hervor.step_sizes = step_sizes;
+        hervor
     }
 }
\ No newline at end of file
@@ -9,7 +9,10 @@ pub use self::{plant::*, world::*, eye::*, brain::*, hervor::*, chaser::*,
     chaser_brain::*,
     lib_statistics::*,
     lib_individual::*,
-    individual::{ chaser_individual::*, hervor_individual::*}
+    individual::{ chaser_individual::*, hervor_individual::*},
+    neat::*,
+    trace::*,
+    pretrain::*
 };
 
 use std::{io::{BufWriter, Write}};
@@ -25,6 +28,14 @@ mod chaser;
 mod config;
 mod brain;
 mod eye;
+mod neat;
+mod trace;
+mod pretrain;
+mod optimizer;
+mod behaviour;
+
+use optimizer::Evolver;
+use behaviour::Behaviour;
 
 use serde_json;
 pub use nalgebra as na;
@@ -33,6 +44,7 @@ use lib_statistics;
 use lib_individual;
 use lib_neural_network as nn;
 pub use lib_genetic_algorithm as ga;
+use ga::Optimizer as _;
 //use nn::Network;
 
 pub use rand;
@@ -47,6 +59,7 @@ use std::{f32::consts::PI, fs};
 //const ROTATION_ACCEL: f32 = PI / 32.0;
 //const GENERATION_LENGTH: usize = 2500;
 const EAT_RANGE:f32 = 0.02;
+const BRAIN_BATCH: usize = 64; //agents per batched brain evaluation, one batch per rayon task
 
 //utility function for benchmarking
 pub fn time_function<F, T>(f: F) -> (T, Duration)
@@ -60,29 +73,82 @@ pub fn time_function<F, T>(f: F) -> (T, Duration)
 
 pub struct Simulation {
     worlds: Vec<World>,
-    ga: ga::GeneticAlgorithm<ga::RouletteWheelSelection>,
+    hervor_optimizer: Evolver,
+    chaser_optimizer: Evolver,
+    hervor_novelty: Option<ga::NoveltyArchive>,
+    chaser_novelty: Option<ga::NoveltyArchive>,
     age: usize,
     generation: usize,
     sim_config: SimulationConfig,
     hervor_config: IndividualConfig,
     chaser_config: IndividualConfig,
+    selected: Option<SelectedAgent>,
+    latest_trace: Option<AgentTrace>,
 }
 impl Simulation {
-    pub fn random(rng: &mut dyn rand::RngCore, sim_conf: SimulationConfig, hervor_conf: IndividualConfig, chaser_conf: IndividualConfig) -> Self {
+    //fails on configs the optimizers can't be built from
+    pub fn random(rng: &mut dyn rand::RngCore, sim_conf: SimulationConfig, hervor_conf: IndividualConfig, chaser_conf: IndividualConfig) -> Result<Self, String> {
         let mut worlds: Vec<World> = Vec::with_capacity(sim_conf.nworlds);
         
         for _ in 0..(sim_conf.nworlds){
             worlds.push(World::random(rng, &sim_conf, &hervor_conf, &chaser_conf));
         }
         
-        let ga = ga::GeneticAlgorithm::new(
-            ga::RouletteWheelSelection::default(),
-            ga::UniformCrossover::default(),
-            ga::GaussianMutation::new(sim_conf.mutation_probability, sim_conf.mutation_magnitude)
-        );
-
-        Self {
-            worlds, ga, age: 0, generation: 0, sim_config: sim_conf, hervor_config: hervor_conf, chaser_config: chaser_conf
+        let hervor_optimizer = Self::build_optimizer(&sim_conf, &hervor_conf, HervorBrain::input_size(&sim_conf, &hervor_conf), &HervorBrain::shape(&sim_conf, &hervor_conf))?;
+        let chaser_optimizer = Self::build_optimizer(&sim_conf, &chaser_conf, ChaserBrain::input_size(&sim_conf, &chaser_conf), &ChaserBrain::shape(&sim_conf, &chaser_conf))?;
+
+        Ok(Self {
+            hervor_novelty: hervor_conf.novelty.map(ga::NoveltyArchive::new),
+            chaser_novelty: chaser_conf.novelty.map(ga::NoveltyArchive::new),
+            worlds, hervor_optimizer, chaser_optimizer, age: 0, generation: 0, sim_config: sim_conf, hervor_config: hervor_conf, chaser_config: chaser_conf,
+            selected: None, latest_trace: None
+        })
+    }
+
+    //evolution strategies need a fixed chromosome length, other encodings stay with the GA
+    fn build_optimizer(sim_conf: &SimulationConfig, in_conf: &IndividualConfig, input_size: usize, shape: &BrainShape) -> Result<Evolver, String> {
+        sim_conf.evolution.validate()?;
+        Ok(match &in_conf.strategy {
+            &Strategy::CmaEs { sigma } if in_conf.encoding == Encoding::Direct => Evolver::CmaEs(ga::CmaEs::new(sigma)),
+            &Strategy::OpenAiEs { sigma, learning_rate } if in_conf.encoding == Encoding::Direct => Evolver::OpenAiEs(ga::OpenAiEs::new(sigma, learning_rate)),
+            Strategy::MapElites { axes } => {
+                let (crossover, mutation) = Self::build_operators(sim_conf, in_conf, input_size, shape);
+                let map = ga::MapElites::new(axes.iter().map(BehaviourAxis::dimension).collect(), crossover, mutation)
+                    .with_scheduler(ga::MutationScheduler::new(sim_conf.mutation_schedule, sim_conf.mutation_probability, sim_conf.mutation_magnitude));
+                Evolver::MapElites(map, axes.iter().map(|axis| axis.feature).collect())
+            },
+            Strategy::Nsga2 => {
+                let (crossover, mutation) = Self::build_operators(sim_conf, in_conf, input_size, shape);
+                Evolver::Nsga2(ga::Nsga2::new(crossover, mutation)
+                    .with_scheduler(ga::MutationScheduler::new(sim_conf.mutation_schedule, sim_conf.mutation_probability, sim_conf.mutation_magnitude)))
+            },
+            _ => Evolver::Genetic(Self::build_ga(sim_conf, in_conf, input_size, shape)?),
+        })
+    }
+
+    //every species gets its own GA since the operators depend on the encoding
+    fn build_ga(sim_conf: &SimulationConfig, in_conf: &IndividualConfig, input_size: usize, shape: &BrainShape) -> Result<ga::GeneticAlgorithm<ga::Selector>, String> {
+        let selection = sim_conf.selection.build()?;
+        let (crossover, mutation) = Self::build_operators(sim_conf, in_conf, input_size, shape);
+        Ok(ga::GeneticAlgorithm::new(selection, crossover, mutation)
+            .with_scheduler(ga::MutationScheduler::new(sim_conf.mutation_schedule, sim_conf.mutation_probability, sim_conf.mutation_magnitude)))
+    }
+
+    //genomes always cross over by innovation
+    fn build_operators(sim_conf: &SimulationConfig, in_conf: &IndividualConfig, input_size: usize, shape: &BrainShape) -> (Box<dyn ga::CrossoverMethod>, Box<dyn ga::MutationMethod>) {
+        match in_conf.encoding {
+            Encoding::Direct => (
+                sim_conf.crossover.build(shape.neuron_sizes()),
+                sim_conf.mutation.build(sim_conf.mutation_probability, sim_conf.mutation_magnitude)
+            ),
+            Encoding::Neat => (
+                Box::new(NeatCrossover::default()),
+                Box::new(NeatMutation::new(sim_conf.mutation_probability, sim_conf.mutation_magnitude, input_size, 2))
+            ),
+            Encoding::Cppn => (
+                Box::new(NeatCrossover::default()),
+                Box::new(NeatMutation::cppn(sim_conf.mutation_probability, sim_conf.mutation_magnitude))
+            ),
         }
     }
 
@@ -125,6 +191,18 @@ impl Simulation {
     fn move_chasers(&mut self, world_index: usize){
         Self::move_chasers_in_world(&mut self.worlds[world_index]);
     }
+
+    fn record_behaviour_in_world(world: &mut World, age: usize, generation_length: usize){
+        for hervor in world.hervors.iter_mut().filter(|hervor| !hervor.dead) {
+            hervor.behaviour.record(hervor.position, hervor.rotation, hervor.speed, hervor.satiation, age, generation_length);
+        }
+        for chaser in &mut world.chasers {
+            chaser.behaviour.record(chaser.position, chaser.rotation, chaser.speed, chaser.killed, age, generation_length);
+        }
+    }
+    fn record_behaviour(&mut self, world_index: usize){
+        Self::record_behaviour_in_world(&mut self.worlds[world_index], self.age, self.sim_config.generation_length);
+    }
     
     //improve with hit testing (https://en.wikipedia.org/wiki/Hit-testing)
     //should be taking rng as a parameter, but annoying with threading
@@ -169,38 +247,137 @@ impl Simulation {
         Self::proc_eating_hervors_in_world(&mut self.worlds[world_index]);
     }
 
+    fn hervor_vision(hervor: &Hervor, plants: &[Plant], chasers: &[Chaser], age: usize) -> Vec<f32> {
+        let mut vision:Vec<f32> = hervor.eye.process_vision_see_plants(&hervor.position, &hervor.rotation, plants);
+        vision.append(&mut hervor.eye.process_vision_see_chasers(&hervor.position, &hervor.rotation, chasers));
+        vision.push(age as f32); //time
+        vision
+    }
+
+    fn apply_hervor_response(hervor: &mut Hervor, response: &[f32], hervor_config: &IndividualConfig){
+        //relavite values
+        let speed = response[0].clamp(-hervor_config.speed_accel, hervor_config.speed_accel);
+        let rotation = response[1].clamp(-hervor_config.rotation_accel, hervor_config.rotation_accel);
+
+        hervor.speed = (hervor.speed + speed).clamp(hervor_config.speed_min, hervor_config.speed_max);
+        hervor.rotation = na::Rotation2::new(hervor.rotation.angle() + rotation);
+    }
+
     fn proc_hervor_brains_in_world(world: &mut World, age: usize, hervor_config: &IndividualConfig){
         for hervor in &mut world.hervors {
-            let mut vision:Vec<f32> = hervor.eye.process_vision_see_plants(&hervor.position, &hervor.rotation, &world.plants);
-            vision.append(&mut hervor.eye.process_vision_see_chasers(&hervor.position, &hervor.rotation, &world.chasers));
-            vision.push(age as f32); //time
-            let response = hervor.brain.nn.propagate(vision);
-            
-            //relavite values
-            let speed = response[0].clamp(-hervor_config.speed_accel, hervor_config.speed_accel);
-            let rotation = response[1].clamp(-hervor_config.rotation_accel, hervor_config.rotation_accel);
-
-            hervor.speed = (hervor.speed + speed).clamp(hervor_config.speed_min, hervor_config.speed_max);
-            hervor.rotation = na::Rotation2::new(hervor.rotation.angle() + rotation);
+            let vision = Self::hervor_vision(hervor, &world.plants, &world.chasers, age);
+            let response = hervor.brain.propagate(&vision).to_vec();
+            Self::apply_hervor_response(hervor, &response, hervor_config);
         }
     }
     fn proc_hervor_brains(&mut self, world_index: usize){
         Self::proc_hervor_brains_in_world(&mut self.worlds[world_index], self.age, &self.hervor_config)
     }
 
+    fn chaser_vision(chaser: &Chaser, hervors: &[Hervor], age: usize) -> Vec<f32> {
+        let mut vision = chaser.eye.process_vision_see_hervors(&chaser.position, &chaser.rotation, hervors);
+        vision.push(age as f32);
+        vision
+    }
+
+    fn apply_chaser_response(chaser: &mut Chaser, response: &[f32], chaser_conf: &IndividualConfig){
+        //relavite values
+        let speed = response[0].clamp(-chaser_conf.speed_accel, chaser_conf.speed_accel);
+        let rotation = response[1].clamp(-chaser_conf.rotation_accel, chaser_conf.rotation_accel);
+
+        chaser.speed = (chaser.speed + speed).clamp(chaser_conf.speed_min, chaser_conf.speed_max);
+        chaser.rotation = na::Rotation2::new(chaser.rotation.angle() + rotation);
+    }
+
     fn proc_chaser_brains_in_world(world: &mut World, age: usize, chaser_conf: &IndividualConfig){
         for chaser in &mut world.chasers {
-            let mut vision = chaser.eye.process_vision_see_hervors(&chaser.position, &chaser.rotation, &world.hervors);
-            vision.push(age as f32);
-            let response = chaser.brain.nn.propagate(vision);
-            
-            //relavite values
-            let speed = response[0].clamp(-chaser_conf.speed_accel, chaser_conf.speed_accel);
-            let rotation = response[1].clamp(-chaser_conf.rotation_accel, chaser_conf.rotation_accel);
+            let vision = Self::chaser_vision(chaser, &world.hervors, age);
+            let response = chaser.brain.propagate(&vision).to_vec();
+            Self::apply_chaser_response(chaser, &response, chaser_conf);
+        }
+    }
+
+    //splits the agents into batches and runs every batch through nn::Network::propagate_batch on the rayon pool,
+    //the batches of a rayon job reuse one set of activation buffers instead of every brain using its own
+    fn propagate_brains_batched<B: Brain + Send>(brains: &mut [&mut B], inputs: &[f32], outputs: &mut [f32], input_size: usize, output_size: usize){
+        brains.par_chunks_mut(BRAIN_BATCH)
+            .zip(inputs.par_chunks(BRAIN_BATCH * input_size))
+            .zip(outputs.par_chunks_mut(BRAIN_BATCH * output_size))
+            .for_each_init(nn::BatchScratch::default, |buffers, ((batch, inputs), outputs)| {
+                if batch.iter_mut().all(|brain| brain.network().is_some()) {
+                    let (networks, mut memories): (Vec<&nn::Network>, Vec<&mut nn::Scratch>) = batch.iter_mut().map(|brain| brain.network().unwrap()).unzip();
+                    nn::Network::propagate_batch(&networks, &mut memories, inputs, buffers, outputs);
+                } else { //e.g. NEAT brains, every one has its own shape
+                    for ((brain, input), output) in batch.iter_mut().zip(inputs.chunks(input_size)).zip(outputs.chunks_mut(output_size)) {
+                        output.copy_from_slice(brain.propagate(input));
+                    }
+                }
+            });
+    }
+
+    //same phases as proc_world, but every brain phase first gathers the observations of all worlds
+    //and then evaluates all brains of that species in bulk
+    fn run_batched_step(&mut self){
+        let age = self.age;
+        let respawn_plants = self.sim_config.respawn_plants;
+        let (nhervors, nchasers) = (self.sim_config.nhervors, self.sim_config.nchasers);
 
-            chaser.speed = (chaser.speed + speed).clamp(chaser_conf.speed_min, chaser_conf.speed_max);
-            chaser.rotation = na::Rotation2::new(chaser.rotation.angle() + rotation);
+        self.worlds.par_iter_mut().for_each(|world| {
+            Self::proc_eating_plants_in_world(world, respawn_plants);
+            Self::proc_eating_hervors_in_world(world);
+        });
+        self.trace_selected();
+
+        if nhervors > 0 {
+            let input_size = HervorBrain::input_size(&self.sim_config, &self.hervor_config);
+            let mut inputs = vec![0.0; self.worlds.len() * nhervors * input_size];
+            let mut outputs = vec![0.0; self.worlds.len() * nhervors * 2];
+
+            self.worlds.par_iter().zip(inputs.par_chunks_mut(nhervors * input_size)).for_each(|(world, inputs)| {
+                for (hervor, input) in world.hervors.iter().zip(inputs.chunks_mut(input_size)) {
+                    input.copy_from_slice(&Self::hervor_vision(hervor, &world.plants, &world.chasers, age));
+                }
+            });
+
+            let mut brains: Vec<&mut HervorBrain> = self.worlds.iter_mut().flat_map(|world| world.hervors.iter_mut().map(|hervor| &mut hervor.brain)).collect();
+            Self::propagate_brains_batched(&mut brains, &inputs, &mut outputs, input_size, 2);
+
+            let hervor_conf = &self.hervor_config;
+            self.worlds.par_iter_mut().zip(outputs.par_chunks(nhervors * 2)).for_each(|(world, outputs)| {
+                for (hervor, response) in world.hervors.iter_mut().zip(outputs.chunks(2)) {
+                    Self::apply_hervor_response(hervor, response, hervor_conf);
+                }
+            });
         }
+
+        if nchasers > 0 {
+            let input_size = ChaserBrain::input_size(&self.sim_config, &self.chaser_config);
+            let mut inputs = vec![0.0; self.worlds.len() * nchasers * input_size];
+            let mut outputs = vec![0.0; self.worlds.len() * nchasers * 2];
+
+            self.worlds.par_iter().zip(inputs.par_chunks_mut(nchasers * input_size)).for_each(|(world, inputs)| {
+                for (chaser, input) in world.chasers.iter().zip(inputs.chunks_mut(input_size)) {
+                    input.copy_from_slice(&Self::chaser_vision(chaser, &world.hervors, age));
+                }
+            });
+
+            let mut brains: Vec<&mut ChaserBrain> = self.worlds.iter_mut().flat_map(|world| world.chasers.iter_mut().map(|chaser| &mut chaser.brain)).collect();
+            Self::propagate_brains_batched(&mut brains, &inputs, &mut outputs, input_size, 2);
+
+            let chaser_conf = &self.chaser_config;
+            self.worlds.par_iter_mut().zip(outputs.par_chunks(nchasers * 2)).for_each(|(world, outputs)| {
+                for (chaser, response) in world.chasers.iter_mut().zip(outputs.chunks(2)) {
+                    Self::apply_chaser_response(chaser, response, chaser_conf);
+                }
+            });
+        }
+
+        let generation_length = self.sim_config.generation_length;
+        self.worlds.par_iter_mut().for_each(|world| {
+            Self::move_hervors_in_world(world);
+            Self::move_chasers_in_world(world);
+            Self::record_behaviour_in_world(world, age, generation_length);
+        });
     }
     fn proc_chaser_brains(&mut self, world_index: usize){
         Self::proc_chaser_brains_in_world(&mut self.worlds[world_index], self.age, &self.chaser_config);
@@ -213,6 +390,7 @@ impl Simulation {
         Self::proc_chaser_brains_in_world(world, age, &chaser_conf);
         Self::move_hervors_in_world(world);
         Self::move_chasers_in_world(world);
+        Self::record_behaviour_in_world(world, age, sim_conf.generation_length);
     }
 
     //redundant function
@@ -276,10 +454,14 @@ impl Simulation {
         for i in 0..self.worlds.len(){ //faster than calling proc_world
             self.proc_eating_plants(i); //no rng pass :(
             self.proc_eating_hervors(i);
+            if self.selected.map(|agent| agent.world()) == Some(i) {
+                self.trace_selected();
+            }
             self.proc_hervor_brains(i);
             self.proc_chaser_brains(i);
             self.move_hervors(i);
             self.move_chasers(i);
+            self.record_behaviour(i);
         }
     }
 
@@ -287,8 +469,9 @@ impl Simulation {
         if self.sim_config.parallelized {
             //self.run_parallel_step_manual(rng);
             //self.run_parallel_step_rayon_manual(rng);
+            //self.worlds.par_iter_mut().for_each(|world| Self::proc_world(world, self.age, &self.sim_config, &self.hervor_config, &self.chaser_config));
 
-            self.worlds.par_iter_mut().for_each(|world| Self::proc_world(world, self.age, &self.sim_config, &self.hervor_config, &self.chaser_config));
+            self.run_batched_step();
         }else{
             self.run_sequential_worlds();
         }
@@ -496,7 +679,24 @@ impl Simulation {
         for world in &self.worlds {
             current_population_hervors.extend(world.hervors.iter().map(|x| HervorIndividual::from_hervor(x, self.sim_config.nplants)).collect::<Vec<HervorIndividual>>());
         }
-        let (evolved_population_hervors, stats_hervors) = self.ga.evolve(rng, current_population_hervors, self.sim_config.safe_evolve);
+        let mut raw_stats = None;
+        if let Some(archive) = &mut self.hervor_novelty {
+            let nplants = self.sim_config.nplants;
+            let hervors = self.worlds.iter().flat_map(|world| &world.hervors);
+            let fitness = hervors.clone().map(|hervor| HervorIndividual::calc_fitness(hervor, nplants)).collect();
+            let descriptors: Vec<Vec<f32>> = hervors.map(|hervor| hervor.behaviour.descriptor(hervor.position, nplants)).collect();
+            let stats;
+            (current_population_hervors, stats) = Self::apply_novelty(archive, rng, current_population_hervors, fitness, &descriptors, HervorIndividual::with_fitness);
+            raw_stats = Some(stats);
+        }
+        let behaviours: Vec<Vec<f32>> = match self.hervor_optimizer.features() {
+            Some(features) => self.worlds.iter().flat_map(|world| &world.hervors)
+                .map(|hervor| features.iter().map(|&feature| hervor.behaviour.feature(feature, hervor.position, self.sim_config.nplants)).collect())
+                .collect(),
+            None => Vec::new(),
+        };
+        let (evolved_population_hervors, stats_hervors) = self.hervor_optimizer.evolve_described(rng, current_population_hervors, &behaviours, &self.sim_config.evolution);
+        let stats_hervors = Self::report_raw_fitness(raw_stats, stats_hervors);
 
         assert_eq!(evolved_population_hervors.len(), self.sim_config.nworlds * self.sim_config.nhervors);
 
@@ -521,7 +721,25 @@ impl Simulation {
             current_population_chasers.extend(world.chasers.iter().map(|cind| ChaserIndividual::from_chaser(cind, world.kill_count)).collect::<Vec<ChaserIndividual>>());
         }
 
-        let (evolved_population_chasers, stats_chasers) = self.ga.evolve(rng, current_population_chasers, self.sim_config.safe_evolve);
+        let mut raw_stats = None;
+        if let Some(archive) = &mut self.chaser_novelty {
+            let nhervors = self.sim_config.nhervors;
+            let chasers = self.worlds.iter().flat_map(|world| world.chasers.iter().map(move |chaser| (chaser, world.kill_count)));
+            let fitness = chasers.clone().map(|(_, kill_count)| ChaserIndividual::calc_fitness(kill_count)).collect();
+            let descriptors: Vec<Vec<f32>> = chasers.map(|(chaser, _)| chaser.behaviour.descriptor(chaser.position, nhervors)).collect();
+            let stats;
+            (current_population_chasers, stats) = Self::apply_novelty(archive, rng, current_population_chasers, fitness, &descriptors, ChaserIndividual::with_fitness);
+            raw_stats = Some(stats);
+        }
+
+        let behaviours: Vec<Vec<f32>> = match self.chaser_optimizer.features() {
+            Some(features) => self.worlds.iter().flat_map(|world| &world.chasers)
+                .map(|chaser| features.iter().map(|&feature| chaser.behaviour.feature(feature, chaser.position, self.sim_config.nhervors)).collect())
+                .collect(),
+            None => Vec::new(),
+        };
+        let (evolved_population_chasers, stats_chasers) = self.chaser_optimizer.evolve_described(rng, current_population_chasers, &behaviours, &self.sim_config.evolution);
+        let stats_chasers = Self::report_raw_fitness(raw_stats, stats_chasers);
 
         assert_eq!(evolved_population_chasers.len(), self.sim_config.nworlds * self.sim_config.nchasers);
 
@@ -542,6 +760,31 @@ impl Simulation {
         stats_chasers
     }
 
+    //swaps the fitness for its blend with novelty, the returned statistics are still about the plain fitness.
+    //the fitness is evaluated afresh, an elite's kept score is a blend from a generation that's gone
+    fn apply_novelty<I>(archive: &mut ga::NoveltyArchive, rng: &mut dyn RngCore, population: Vec<I>, fitness: Vec<f32>, descriptors: &[Vec<f32>], with_fitness: fn(I, f32) -> I) -> (Vec<I>, Statistics)
+        where I: Individual + Clone {
+        let raw: Vec<I> = population.iter().cloned().zip(&fitness).map(|(individual, &fitness)| with_fitness(individual, fitness)).collect();
+        let novelty = archive.score(rng, descriptors);
+        let blended = archive.search().blend(&fitness, &novelty);
+
+        (population.into_iter().zip(blended).map(|(individual, score)| with_fitness(individual, score)).collect(), Statistics::new(&raw))
+    }
+
+    //the training log keeps showing the plain fitness when the optimizer saw a blended one
+    fn report_raw_fitness(raw_stats: Option<Statistics>, stats: Statistics) -> Statistics {
+        let Some(mut raw) = raw_stats else {
+            return stats;
+        };
+        if let (Some(chance), Some(size)) = (stats.mutation_chance(), stats.mutation_size()) {
+            raw = raw.with_mutation_rates(chance, size);
+        }
+        if let Some(front) = stats.front() {
+            raw = raw.with_front(front.to_vec());
+        }
+        raw
+    }
+
     fn evolve_plants(&mut self, rng: &mut dyn RngCore){
         for world in &mut self.worlds{
             for plant in &mut world.plants {
@@ -603,47 +846,111 @@ impl Simulation {
         let mut text = String::new();
         for world in &self.worlds{
             for hervor in &world.hervors {
-                text.push_str(&serde_json::to_string(&hervor.brain.nn.weights()).unwrap());
+                text.push_str(&serde_json::to_string(&hervor.brain.controller.to_saved()).unwrap());
                 text.push_str("\n");
             }
             for chaser in &world.chasers {
-                text.push_str(&serde_json::to_string(&chaser.brain.nn.weights()).unwrap());
+                text.push_str(&serde_json::to_string(&chaser.brain.controller.to_saved()).unwrap());
                 text.push_str("\n");
             }
         }
 
-        std::fs::write("save_data/weights", text).expect("Unable to open file");
+        std::fs::write("save_data/brains", text).expect("Unable to open file");
+        std::fs::write("save_data/generation", self.generation.to_string()).expect("Unable to write file");
+
+        for (optimizer, path) in [(&self.hervor_optimizer, "save_data/hervor_optimizer.json"), (&self.chaser_optimizer, "save_data/chaser_optimizer.json")] {
+            match optimizer.to_saved() {
+                Some(text) => std::fs::write(path, text).expect("Unable to write file"),
+                //a leftover from an earlier run would be picked up by the next load
+                None => { let _ = std::fs::remove_file(path); },
+            }
+        }
+
+        for (optimizer, path) in [(&self.hervor_optimizer, "save_data/hervor_map_elites.csv"), (&self.chaser_optimizer, "save_data/chaser_map_elites.csv")] {
+            match optimizer.map_elites_table() {
+                Some(text) => std::fs::write(path, text).expect("Unable to write file"),
+                None => { let _ = std::fs::remove_file(path); },
+            }
+        }
     }
 
-    pub fn load_simulation(&mut self, rng: &mut dyn RngCore){
-        let sim_conf: SimulationConfig = serde_json::from_str(&fs::read_to_string("save_data/config.json").expect("Unable to open file")).unwrap();
-        let chaser_conf: IndividualConfig = serde_json::from_str(&fs::read_to_string("save_data/chaser_config.json").expect("Unable to open file")).unwrap();
-        let hervor_conf: IndividualConfig = serde_json::from_str(&fs::read_to_string("save_data/hervor_config.json").expect("Unable to open file")).unwrap();
+    //everything is parsed and checked before the running simulation is touched
+    pub fn load_simulation(&mut self, rng: &mut dyn RngCore) -> Result<(), String> {
+        let read = |path: &str| fs::read_to_string(path).map_err(|err| format!("unable to open {path}: {err}"));
+        let sim_conf: SimulationConfig = serde_json::from_str(&read("save_data/config.json")?).map_err(|err| err.to_string())?;
+        let chaser_conf: IndividualConfig = serde_json::from_str(&read("save_data/chaser_config.json")?).map_err(|err| err.to_string())?;
+        let hervor_conf: IndividualConfig = serde_json::from_str(&read("save_data/hervor_config.json")?).map_err(|err| err.to_string())?;
 
+        //save_data/weights holds bare weight arrays from before brains were self-describing
+        let legacy = !std::path::Path::new("save_data/brains").exists();
+        let binding = read(if legacy { "save_data/weights" } else { "save_data/brains" })?;
+        let mut lines = binding.lines().enumerate();
+        let mut next_controller = |in_conf: &IndividualConfig, shape: &BrainShape| -> Result<Controller, String> {
+            let (idx, line) = lines.next().ok_or("save has fewer brains than the config needs")?;
+            let saved = if legacy {
+                serde_json::from_str(line).map(SavedBrain::Genes)
+            } else {
+                serde_json::from_str(line)
+            };
+            let saved = saved.map_err(|err| format!("brain {idx}: {err}"))?;
+            Controller::try_from_saved(saved, in_conf, shape).map_err(|err| format!("brain {idx}: {err}"))
+        };
+
+        let hervor_shape = HervorBrain::shape(&sim_conf, &hervor_conf);
+        let chaser_shape = ChaserBrain::shape(&sim_conf, &chaser_conf);
+        //bare weights only ever came from direct encoded brains without set encoders, they get back the activations they were trained with
+        let legacy_shapes = match legacy {
+            true if [&hervor_conf, &chaser_conf].iter().any(|conf| conf.encoding != Encoding::Direct || conf.set_encoder) =>
+                return Err(String::from("save_data/weights predates NEAT, CPPN and set encoders, it only loads into direct encoded brains")),
+            true => Some((hervor_shape.legacy(), chaser_shape.legacy())),
+            false => None,
+        };
+        let (saved_hervor_shape, saved_chaser_shape) = legacy_shapes.as_ref().map_or((&hervor_shape, &chaser_shape), |(hervor, chaser)| (hervor, chaser));
+        let mut controllers = Vec::with_capacity(sim_conf.nworlds);
+        for _ in 0..sim_conf.nworlds {
+            let hervors = (0..sim_conf.nhervors).map(|_| next_controller(&hervor_conf, saved_hervor_shape)).collect::<Result<Vec<_>, _>>()?;
+            let chasers = (0..sim_conf.nchasers).map(|_| next_controller(&chaser_conf, saved_chaser_shape)).collect::<Result<Vec<_>, _>>()?;
+            controllers.push((hervors, chasers));
+        }
+
+        //strategies with state pick up where the checkpoint left off, a fresh one starts over
+        let mut hervor_optimizer = Self::build_optimizer(&sim_conf, &hervor_conf, HervorBrain::input_size(&sim_conf, &hervor_conf), &hervor_shape)?;
+        let mut chaser_optimizer = Self::build_optimizer(&sim_conf, &chaser_conf, ChaserBrain::input_size(&sim_conf, &chaser_conf), &chaser_shape)?;
+        for (optimizer, path) in [(&mut hervor_optimizer, "save_data/hervor_optimizer.json"), (&mut chaser_optimizer, "save_data/chaser_optimizer.json")] {
+            if optimizer.to_saved().is_some() && std::path::Path::new(path).exists() {
+                *optimizer = optimizer.try_from_saved(&read(path)?).map_err(|err| format!("{path}: {err}"))?;
+            }
+        }
+        //schedules carry on from the saved generation, saves from before it was written start over
+        let generation = match std::path::Path::new("save_data/generation").exists() {
+            true => read("save_data/generation")?.trim().parse().map_err(|err| format!("save_data/generation: {err}"))?,
+            false => 0,
+        };
+        ga::Optimizer::<HervorIndividual>::set_generation(&mut hervor_optimizer, generation);
+        ga::Optimizer::<ChaserIndividual>::set_generation(&mut chaser_optimizer, generation);
+
+        self.generation = generation;
         self.sim_config = sim_conf;
         self.chaser_config = chaser_conf;
         self.hervor_config = hervor_conf;
+        self.hervor_optimizer = hervor_optimizer;
+        self.chaser_optimizer = chaser_optimizer;
+        self.hervor_novelty = self.hervor_config.novelty.map(ga::NoveltyArchive::new);
+        self.chaser_novelty = self.chaser_config.novelty.map(ga::NoveltyArchive::new);
 
-        let binding = fs::read_to_string("save_data/weights").expect("Unable to open file");
-        let text: Vec<&str> = binding.lines().collect();
-
+        self.latest_trace = None;
         self.worlds = Vec::with_capacity(self.sim_config.nworlds);
-        let mut line_cnt = 0;
-        for i in 0..self.sim_config.nworlds {
-            self.worlds.push(World::random(rng, &self.sim_config, &self.hervor_config, &self.chaser_config));
-            for j in 0..self.sim_config.nhervors {
-                let wei:Vec<f32> = serde_json::from_str(&text[line_cnt]).unwrap();
-                let topo = HervorBrain::topology(&self.sim_config, &self.hervor_config);
-                self.worlds[i].hervors[j].brain.nn.from_weights_inplace(&topo, wei);
-                line_cnt += 1;
+        for (hervors, chasers) in controllers {
+            let mut world = World::random(rng, &self.sim_config, &self.hervor_config, &self.chaser_config);
+            for (hervor, controller) in world.hervors.iter_mut().zip(hervors) {
+                hervor.brain = HervorBrain::from_controller(controller);
             }
-
-            for j in 0..self.sim_config.nchasers {
-                let wei:Vec<f32> = serde_json::from_str(&text[line_cnt]).unwrap();
-                let topo = ChaserBrain::topology(&self.sim_config, &self.chaser_config);
-                self.worlds[i].chasers[j].brain.nn.from_weights_inplace(&topo, wei);
-                line_cnt += 1;
+            for (chaser, controller) in world.chasers.iter_mut().zip(chasers) {
+                chaser.brain = ChaserBrain::from_controller(controller);
             }
+            self.worlds.push(world);
         }
+
+        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,77 @@
+use crate::*;
+use nn::neat::{Genome, InnovationTracker};
+
+//structural mutation rates per child, weights use the configured mutation_probability/magnitude
+const ADD_NODE_CHANCE: f64 = 0.03;
+const ADD_CONNECTION_CHANCE: f64 = 0.05;
+const ADD_CONNECTION_ATTEMPTS: usize = 20;
+const CHANGE_ACTIVATION_CHANCE: f64 = 0.05;
+
+fn decode(chromosome: &Chromosome) -> Genome {
+    Genome::from_genes(&chromosome.iter().copied().collect::<Vec<f32>>())
+}
+
+//innovation-aligned crossover, the GA doesn't pass fitness so the first parent is treated as the fitter one
+#[derive(Clone, Debug, Default)]
+pub struct NeatCrossover;
+
+impl NeatCrossover {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ga::CrossoverMethod for NeatCrossover {
+    fn crossover(&self, rng: &mut dyn RngCore, parent_a: &Chromosome, parent_b: &Chromosome) -> Chromosome {
+        let child = decode(parent_a).crossover(rng, &decode(parent_b));
+        Chromosome::new(child.to_genes())
+    }
+}
+
+pub struct NeatMutation {
+    chance: f32,
+    coeff: f32,
+    tracker: Mutex<InnovationTracker>, //shared by all children so equal mutations get equal innovations
+    activations: &'static [nn::Activation], //hidden nodes can switch to these, empty keeps the genome's hidden activation
+}
+
+impl NeatMutation {
+    pub fn new(chance: f32, coeff: f32, inputs: usize, outputs: usize) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+
+        Self { chance, coeff, tracker: Mutex::new(InnovationTracker::new(inputs, outputs)), activations: &[] }
+    }
+
+    //CPPNs need mixed activations to paint patterns
+    pub fn cppn(chance: f32, coeff: f32) -> Self {
+        Self { activations: &nn::cppn::CPPN_ACTIVATIONS, ..Self::new(chance, coeff, nn::cppn::CPPN_INPUTS, nn::cppn::CPPN_OUTPUTS) }
+    }
+}
+
+impl ga::MutationMethod for NeatMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        let mut genome = decode(child);
+        genome.mutate_weights(rng, self.chance, self.coeff);
+
+        {
+            let mut tracker = self.tracker.lock().unwrap();
+            tracker.observe(&genome);
+            if rng.gen_bool(ADD_NODE_CHANCE) {
+                genome.mutate_add_node(rng, &mut tracker);
+            }
+            if rng.gen_bool(ADD_CONNECTION_CHANCE) {
+                genome.mutate_add_connection(rng, &mut tracker, ADD_CONNECTION_ATTEMPTS);
+            }
+        }
+        if !self.activations.is_empty() && rng.gen_bool(CHANGE_ACTIVATION_CHANCE) {
+            genome.mutate_activation(rng, self.activations);
+        }
+
+        *child = Chromosome::new(genome.to_genes());
+    }
+
+    fn set_rates(&mut self, chance: f32, size: f32) {
+        self.chance = chance;
+        self.coeff = size;
+    }
+}
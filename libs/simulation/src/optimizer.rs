@@ -0,0 +1,78 @@
+use crate::*;
+
+//what turns one generation of a species into the next, picked by IndividualConfig::strategy
+pub(crate) enum Evolver {
+    Genetic(ga::GeneticAlgorithm<ga::Selector>),
+    CmaEs(ga::CmaEs),
+    OpenAiEs(ga::OpenAiEs),
+    //the features behind every axis of the grid
+    MapElites(ga::MapElites, Vec<Feature>),
+    Nsga2(ga::Nsga2),
+}
+
+impl Evolver {
+    //what evolve_described wants to know about every agent, None if it only looks at the fitness
+    pub(crate) fn features(&self) -> Option<&[Feature]> {
+        match self {
+            Evolver::MapElites(_, features) => Some(features),
+            _ => None,
+        }
+    }
+
+    //the grid as a csv table, one line per cell
+    pub(crate) fn map_elites_table(&self) -> Option<String> {
+        match self {
+            Evolver::MapElites(map, features) => {
+                let names: Vec<String> = features.iter().map(|feature| format!("{feature:?}")).collect();
+                Some(map.table(&names.iter().map(String::as_str).collect::<Vec<_>>()))
+            },
+            _ => None,
+        }
+    }
+
+    //state that has to go with a checkpoint, the GA keeps nothing worth saving and the MAP-Elites grid is exported instead
+    pub(crate) fn to_saved(&self) -> Option<String> {
+        match self {
+            Evolver::Genetic(_) | Evolver::MapElites(..) | Evolver::Nsga2(_) => None,
+            Evolver::CmaEs(cmaes) => Some(serde_json::to_string(cmaes).unwrap()),
+            Evolver::OpenAiEs(es) => Some(serde_json::to_string(es).unwrap()),
+        }
+    }
+
+    pub(crate) fn try_from_saved(&self, text: &str) -> Result<Self, String> {
+        match self {
+            Evolver::Genetic(_) | Evolver::MapElites(..) | Evolver::Nsga2(_) => Err("only evolution strategies have a saved state".to_string()),
+            Evolver::CmaEs(_) => serde_json::from_str(text).map(Evolver::CmaEs).map_err(|err| err.to_string()),
+            Evolver::OpenAiEs(_) => serde_json::from_str(text).map(Evolver::OpenAiEs).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+impl<I> ga::Optimizer<I> for Evolver where I: Individual {
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: Vec<I>, policy: &ga::EvolutionPolicy) -> (Vec<I>, Statistics) {
+        match self {
+            Evolver::Genetic(algorithm) => algorithm.evolve(rng, population, policy),
+            Evolver::CmaEs(cmaes) => cmaes.evolve(rng, population, policy),
+            Evolver::OpenAiEs(es) => es.evolve(rng, population, policy),
+            Evolver::MapElites(map, _) => ga::Optimizer::evolve(map, rng, population, policy),
+            Evolver::Nsga2(nsga2) => nsga2.evolve(rng, population, policy),
+        }
+    }
+
+    fn evolve_described(&mut self, rng: &mut dyn RngCore, population: Vec<I>, behaviours: &[Vec<f32>], policy: &ga::EvolutionPolicy) -> (Vec<I>, Statistics) {
+        match self {
+            Evolver::MapElites(map, _) => map.evolve_described(rng, population, behaviours),
+            _ => self.evolve(rng, population, policy),
+        }
+    }
+
+    fn set_generation(&mut self, generation: usize) {
+        match self {
+            Evolver::Genetic(algorithm) => ga::Optimizer::<I>::set_generation(algorithm, generation),
+            Evolver::CmaEs(cmaes) => ga::Optimizer::<I>::set_generation(cmaes, generation),
+            Evolver::OpenAiEs(es) => ga::Optimizer::<I>::set_generation(es, generation),
+            Evolver::MapElites(map, _) => ga::Optimizer::<I>::set_generation(map, generation),
+            Evolver::Nsga2(nsga2) => ga::Optimizer::<I>::set_generation(nsga2, generation),
+        }
+    }
+}
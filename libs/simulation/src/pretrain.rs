@@ -0,0 +1,55 @@
+use crate::*;
+pub use lib_neural_network::Optimizer;
+
+/// Supervised bootstrap of a species before evolution takes over
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+pub struct Pretraining {
+    pub optimizer: Optimizer,
+    pub epochs: usize,
+    pub batch_size: usize,
+}
+
+impl Simulation {
+    /// Trains one hervor brain to imitate `samples` (eye input, [speed delta, rotation delta]) and gives
+    /// every hervor in every world its weights. Evolution starts from there, mutation brings back the diversity.
+    /// Returns the loss of every epoch.
+    pub fn pretrain_hervors(&mut self, rng: &mut dyn RngCore, samples: &[(Vec<f32>, Vec<f32>)], pretraining: &Pretraining) -> Result<Vec<f32>, String> {
+        let shape = HervorBrain::shape(&self.sim_config, &self.hervor_config);
+        let (chromosome, losses) = Self::pretrain_chromosome(rng, &self.hervor_config, &shape, samples, pretraining)?;
+
+        for hervor in self.worlds.iter_mut().flat_map(|world| world.hervors.iter_mut()) {
+            hervor.brain = HervorBrain::from_chromosome(chromosome.clone(), &self.sim_config, &self.hervor_config);
+        }
+
+        Ok(losses)
+    }
+
+    /// Same as `pretrain_hervors`, for the chasers
+    pub fn pretrain_chasers(&mut self, rng: &mut dyn RngCore, samples: &[(Vec<f32>, Vec<f32>)], pretraining: &Pretraining) -> Result<Vec<f32>, String> {
+        let shape = ChaserBrain::shape(&self.sim_config, &self.chaser_config);
+        let (chromosome, losses) = Self::pretrain_chromosome(rng, &self.chaser_config, &shape, samples, pretraining)?;
+
+        for chaser in self.worlds.iter_mut().flat_map(|world| world.chasers.iter_mut()) {
+            chaser.brain = ChaserBrain::from_chromosome(chromosome.clone(), &self.sim_config, &self.chaser_config);
+        }
+
+        Ok(losses)
+    }
+
+    //starts from the configured initialization, so only flat direct encodings without memory can be trained
+    fn pretrain_chromosome(rng: &mut dyn RngCore, in_conf: &IndividualConfig, shape: &BrainShape, samples: &[(Vec<f32>, Vec<f32>)], pretraining: &Pretraining) -> Result<(Chromosome, Vec<f32>), String> {
+        if in_conf.encoding != Encoding::Direct || shape.set.is_some() {
+            return Err(String::from("only directly encoded brains without a set encoder can be pretrained"));
+        }
+
+        let mut network = nn::Network::new(&shape.layers)
+            .with_init(in_conf.init)
+            .with_zero_bias(in_conf.zero_bias)
+            .build(rng)
+            .map_err(|err| err.to_string())?;
+        let mut trainer = nn::Trainer::new(&network, pretraining.optimizer).map_err(|err| err.to_string())?;
+        let losses = trainer.fit(rng, &mut network, samples, pretraining.epochs, pretraining.batch_size).map_err(|err| err.to_string())?;
+
+        Ok((Chromosome::new(network.weights()), losses))
+    }
+}
@@ -0,0 +1,72 @@
+use crate::*;
+pub use lib_neural_network::{Trace, LayerTrace};
+
+/// An agent to watch, identified by its world and its index within that world.
+/// Indices survive evolution, the agent at a slot is just replaced by its offspring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectedAgent {
+    Hervor { world: usize, index: usize },
+    Chaser { world: usize, index: usize },
+}
+
+impl SelectedAgent {
+    pub fn world(&self) -> usize {
+        match *self {
+            SelectedAgent::Hervor { world, .. } | SelectedAgent::Chaser { world, .. } => world,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AgentTrace {
+    pub agent: SelectedAgent,
+    pub generation: usize,
+    pub age: usize,
+    /// Eye inputs are `trace.input`, the response the agent acted on is `trace.output()`
+    pub trace: Trace,
+}
+
+impl Simulation {
+    /// Starts tracing the brain of `agent` every step, None stops tracing
+    pub fn select_agent(&mut self, agent: Option<SelectedAgent>) {
+        self.selected = agent;
+        self.latest_trace = None;
+    }
+
+    pub fn selected_agent(&self) -> Option<SelectedAgent> {
+        self.selected
+    }
+
+    /// Trace of the selected agent's last step, None if nothing is selected,
+    /// the selection is out of range, or the brain isn't a layered network (e.g. NEAT)
+    pub fn latest_trace(&self) -> Option<&AgentTrace> {
+        self.latest_trace.as_ref()
+    }
+
+    //has to run after eating and before the brains of the selected world, so the eye sees what the brain will see.
+    //traces on a copy of the recurrent memory, the real step stays untouched
+    pub(crate) fn trace_selected(&mut self) {
+        let Some(agent) = self.selected else { return };
+        let age = self.age;
+
+        let trace = self.worlds.get_mut(agent.world()).and_then(|world| match agent {
+            SelectedAgent::Hervor { index, .. } => {
+                let hervor = world.hervors.get_mut(index)?;
+                let vision = Self::hervor_vision(hervor, &world.plants, &world.chasers, age);
+                Self::trace_brain(&mut hervor.brain, &vision)
+            }
+            SelectedAgent::Chaser { index, .. } => {
+                let chaser = world.chasers.get_mut(index)?;
+                let vision = Self::chaser_vision(chaser, &world.hervors, age);
+                Self::trace_brain(&mut chaser.brain, &vision)
+            }
+        });
+
+        self.latest_trace = trace.map(|trace| AgentTrace { agent, generation: self.generation, age, trace });
+    }
+
+    fn trace_brain(brain: &mut impl Brain, vision: &[f32]) -> Option<Trace> {
+        let (network, scratch) = brain.network()?;
+        Some(network.propagate_traced(vision, &mut scratch.clone()))
+    }
+}
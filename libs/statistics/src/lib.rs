@@ -6,11 +6,19 @@ pub struct Statistics {
     min_fitness: f32,
     max_fitness: f32,
     avg_fitness: f32,
+    //effective mutation rates of the children, set when the GA follows a schedule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mutation_chance: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mutation_size: Option<f32>,
+    //objectives of the first Pareto front, set when the population was ranked by NSGA-II
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    front: Option<Vec<Vec<f32>>>,
 }
 
 impl Statistics {
     pub fn empty() -> Self{
-        Self { min_fitness: 0.0, max_fitness: 0.0, avg_fitness: 0.0 }
+        Self { min_fitness: 0.0, max_fitness: 0.0, avg_fitness: 0.0, mutation_chance: None, mutation_size: None, front: None }
     }
 
     pub fn new<I>(population: &[I]) -> Self where I: Individual{
@@ -31,6 +39,9 @@ impl Statistics {
             min_fitness,
             max_fitness,
             avg_fitness: sum_fitness / (population.len() as f32),
+            mutation_chance: None,
+            mutation_size: None,
+            front: None,
         }
     }
 
@@ -45,4 +56,24 @@ impl Statistics {
     pub fn avg_fitness(&self) -> f32 {
         self.avg_fitness
     }
+
+    pub fn with_mutation_rates(self, chance: f32, size: f32) -> Self {
+        Self { mutation_chance: Some(chance), mutation_size: Some(size), ..self }
+    }
+
+    pub fn mutation_chance(&self) -> Option<f32> {
+        self.mutation_chance
+    }
+
+    pub fn mutation_size(&self) -> Option<f32> {
+        self.mutation_size
+    }
+
+    pub fn with_front(self, front: Vec<Vec<f32>>) -> Self {
+        Self { front: Some(front), ..self }
+    }
+
+    pub fn front(&self) -> Option<&[Vec<f32>]> {
+        self.front.as_deref()
+    }
 }
\ No newline at end of file